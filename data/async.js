@@ -0,0 +1,46 @@
+// async functions, await and generators
+async function load(id) {
+  const row = await query(id);
+  return row;
+}
+
+const save = async (row) => {
+  await query(row.id);
+};
+
+const remove = async id => query(id);
+
+function* ids() {
+  yield* range(0, 10);
+  yield next();
+}
+
+function query(id) {
+  return db.run(id);
+}
+
+function range(from, to) {
+  return db.range(from, to);
+}
+
+function next() {
+  return db.next();
+}
+
+class Store {
+  async get(id) {
+    return await load(id);
+  }
+
+  *[Symbol.iterator]() {}
+
+  async *rows() {}
+}
+
+async function foo() {
+  await load(1);
+  await save({ id: 2 });
+  await remove(3);
+  for (const id of ids()) {
+  }
+}
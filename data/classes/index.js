@@ -0,0 +1,11 @@
+const UserService = require("./user-service");
+
+function foo(user) {
+  UserService.create(user);
+  new UserService(user.db).save(user);
+}
+
+function bar(user) {
+  const svc = new UserService(user.db);
+  svc.validate(user);
+}
@@ -0,0 +1,29 @@
+class UserService extends BaseService {
+  constructor(db) {
+    super(db);
+    this.db = db;
+  }
+
+  static create(user) {
+    return new UserService(user.db);
+  }
+
+  save(user) {
+    this.validate(user);
+    this.db.insert(user);
+  }
+
+  validate(user) {
+    check(user.name);
+  }
+
+  get size() {
+    return this.db.count();
+  }
+}
+
+function check(name) {
+  log(name);
+}
+
+module.exports = UserService;
@@ -0,0 +1,13 @@
+function foo() {
+  // don't call baz()
+  pin = 'pin'; // pin
+  bar();
+}
+
+function bar() {
+  pin = bar;
+}
+
+function baz() {
+  pin = baz;
+}
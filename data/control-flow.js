@@ -0,0 +1,45 @@
+function foo(user) {
+  if (isAdmin(user)) {
+    grant(user);
+  } else if (user.guest)
+    deny(user);
+  else {
+    log(user);
+  }
+
+  for (const item of list(user)) {
+    audit(user, item);
+  }
+
+  while (user.pending) step(user);
+
+  do {
+    retry(user);
+  } while (user.failed);
+
+  switch (user.role) {
+    case "admin":
+      grant(user);
+      break;
+    case 2:
+    default:
+      log(user);
+  }
+
+  try {
+    save(user);
+  } catch (err) {
+    report(err, user);
+  } finally {
+    close(user);
+  }
+}
+
+function isAdmin(user) {
+  return user.admin;
+}
+
+function oneLine(a) {
+  if (a) { target(1); } else { target(2); }
+  try { target(3); } catch { target(4); } finally { target(5); }
+}
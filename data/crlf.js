@@ -0,0 +1,6 @@
+function foo(user) {
+  bar(user);
+}
+
+function bar(user) {  user.seen = true;}
+// end function baz(user) {}
@@ -0,0 +1,13 @@
+function baz(o) {
+  o.visited = true;
+  return helper(1, o);
+}
+
+function helper(n, item) {
+  log(item);
+}
+
+function foo() {
+  const obj = {};
+  baz(obj);
+}
@@ -0,0 +1,12 @@
+let total;
+const rate = 2, apply = (n) => scale(n, rate);
+var first = 1,
+  second = scale(first, 2);
+
+function scale(n, by) {
+  return n * by;
+}
+
+function foo() {
+  apply(total);
+}
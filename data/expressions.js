@@ -0,0 +1,31 @@
+// calls nested inside expressions
+function foo(x) {
+  const y = double(x) + 1;
+  if (!isValid(x) && x > 0) {
+    log(y);
+  }
+  const z = cached || compute(x);
+  const label = x > 1 ? plural(x) : singular(x);
+  const items = [first(x), ...rest(x)];
+  handlers[pick(x)] = x;
+  total += sum(wrap(x), 2);
+  fetchAll(x)
+    .then((rows) => store(rows))
+    .catch(report);
+  return format(x);
+}
+
+function double(n) { return n * 2; }
+function isValid(n) { return typeof n === "number"; }
+function log(n) { return n; }
+function compute(n) { return n; }
+function plural(n) { return n; }
+function singular(n) { return n; }
+function first(n) { return n; }
+function rest(n) { return [n]; }
+function pick(n) { return n; }
+function sum(a, b) { return a + b; }
+function wrap(n) { return n; }
+function fetchAll(n) { return n; }
+function store(rows) { return rows; }
+function format(n) { return String(n); }
@@ -0,0 +1,4 @@
+function foo(obj) {
+  obj.baz = 1;
+  objXbaz = 2;
+}
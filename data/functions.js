@@ -0,0 +1,26 @@
+// arrow functions and function expressions
+const double = x => scale(x, 2);
+const half = (x) =>
+  scale(x, 0.5);
+const triple = function (x) {
+  return scale(x, 3);
+};
+const square = function sq(x) {
+  return x * x;
+};
+
+function scale(x, by) {
+  return x * by;
+}
+
+function other() {
+  const unrelated = scale;
+}
+
+function foo() {
+  double(1);
+  half(2);
+  triple(3);
+  square(4);
+  [1, 2].map((n) => scale(n, 1));
+}
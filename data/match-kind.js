@@ -0,0 +1,8 @@
+function foo() {
+  // the user object
+  const user = "user";
+  log(user);
+  /* user
+     user */
+  return 'user';
+}
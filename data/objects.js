@@ -0,0 +1,34 @@
+const key = "id";
+
+function load(pin) {
+  return pin;
+}
+
+function find(pin) {
+  return pin;
+}
+
+const api = {
+  base: "/users",
+  [key]: 1,
+  load,
+  fetch: find,
+  get(pin) {
+    return load(pin);
+  },
+  put: function (pin) {
+    return pin;
+  },
+  remove: (pin) => {
+    return pin;
+  },
+  ...defaults,
+};
+
+function foo() {
+  api.get(1);
+  api.put(2);
+  api.remove(3);
+  api.fetch(4);
+  api.missing(5);
+}
@@ -0,0 +1,19 @@
+function foo(user, ...rest) {
+  const name = (user);
+  const check = (a, b) => {
+    return a === b && a !== null;
+  };
+  const total = (count ?? 0) + 1;
+  user?.profile?.reload(user);
+  load?.(user);
+  notify(...rest, user);
+  user.visits += 1;
+}
+
+function load(user) {
+  log(user);
+}
+
+function notify(first, second) {
+  second.seen = true;
+}
@@ -0,0 +1,12 @@
+function broken(user) {
+  user.name = "broken";
+  log(user;
+}
+
+const { name } user;
+
+function after(user) {
+  log(user);
+}
+
+module.exports = { broken, after };
@@ -0,0 +1,5 @@
+function fine(user) {
+  user.name = "fine";
+}
+
+module.exports = { fine };
@@ -0,0 +1,8 @@
+const { broken, after } = require("./broken");
+const { fine } = require("./fine");
+
+function foo() {
+  broken(user);
+  after(user);
+  fine(user);
+}
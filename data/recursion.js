@@ -0,0 +1,17 @@
+function walk(node) {
+  visit(node);
+  walk(node.next);
+}
+
+function visit(node) {
+  log(node);
+  check(node);
+}
+
+function check(node) {
+  visit(node.child);
+}
+
+function foo(root) {
+  walk(root);
+}
@@ -0,0 +1,13 @@
+function validate(input) {
+  const prefix = /^\/*[a-z]+/;
+  check(input);
+  return /[/]/.test(input) && input.length / 2 > 1;
+}
+
+function check(input) {
+  log(input);
+}
+
+function foo() {
+  validate(input);
+}
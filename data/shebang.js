@@ -0,0 +1,9 @@
+﻿#!/usr/bin/env node
+// don't break
+function main(args) {
+  run(args);
+}
+
+function run(args) {
+  console.log(args);
+}
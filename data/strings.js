@@ -0,0 +1,9 @@
+function foo(user) {
+  log('don\'t touch', user);
+  log("say \"hi\" to", user);
+  bar(user);
+}
+
+function bar(user) {
+  user.seen = true;
+}
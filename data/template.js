@@ -0,0 +1,14 @@
+function format(user) {
+  return user.name;
+}
+
+function greet(user) {
+  return `hello`;
+}
+
+function foo() {
+  log(`Dear ${format(user)},
+  welcome`);
+  const s = `${greet(user)} and {not a call()}`;
+  user.done = true;
+}
@@ -0,0 +1,14 @@
+if (ready) {
+  start();
+}
+
+if (done) {
+  stop();
+}
+
+function load(value) {
+  return value.pin;
+}
+
+load(config);
+pin();
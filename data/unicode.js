@@ -0,0 +1,14 @@
+// Überprüfung — ünïcödé comment ✓
+function café($scope, _$el) {
+  const grüße = '¡hola! 👋';
+  $scope.émoji = `✓ ${_$el}`;
+  naïve($scope);
+}
+
+function naïve($scope) {
+  $scope.done = true;
+}
+
+function foo() {
+  café($scope, el);
+}
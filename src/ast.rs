@@ -2,38 +2,86 @@
 pub enum ASTNode {
     Program(Program),
     FunctionStatement(FunctionStatement),
+    ClassDeclaration(ClassDeclaration),
     BlockStatement(BlockStatement),
+    IfStatement(IfStatement),
+    ForStatement(ForStatement),
+    WhileStatement(WhileStatement),
+    DoWhileStatement(WhileStatement),
+    SwitchStatement(SwitchStatement),
+    TryStatement(TryStatement),
     CallExpression(CallExpression),
+    NewExpression(CallExpression),
     VariableExpression(VariableExpression),
     ObjectPattern(ObjectPattern),
+    ObjectExpression(ObjectExpression),
+    ObjectProperty(ObjectProperty),
     ExportStatement(ObjectPattern),
+    /// `module.exports = name`
+    ExportIdentifier(Identifier),
     Identifier(Identifier),
     MemberExpression(MemberExpression),
+    /// `base[property]`
+    ComputedMemberExpression(ComputedMemberExpression),
+    BinaryExpression(BinaryExpression),
+    UnaryExpression(UnaryExpression),
+    ConditionalExpression(ConditionalExpression),
+    AssignmentExpression(BinaryExpression),
+    ArrayExpression(ArrayExpression),
+    /// `a, b` inside parentheses.
+    SequenceExpression(ArrayExpression),
+    StringLiteral(StringLiteral),
+    NumericLiteral(NumericLiteral),
+    TemplateLiteral(TemplateLiteral),
+    RegexLiteral(RegexLiteral),
+    SpreadElement(SpreadElement),
+    AwaitExpression(AwaitExpression),
+    YieldExpression(YieldExpression),
+    Unknown(Unknown),
 }
 
-type Line = usize;
+pub use crate::span::{Position, Span};
 
 #[derive(Debug, Clone)]
 pub struct Program {
     pub lines: Box<Vec<ASTNode>>,
-    pub start: Line,
-    pub end: Line,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionStatement {
+    /// Empty for an anonymous function that isn't assigned to anything.
     pub name: String,
+    pub params: Vec<ASTNode>,
+    /// A `BlockStatement`, also for an arrow function's expression body.
     pub body: Box<ASTNode>,
-    pub start: Line,
-    pub end: Line,
+    pub is_async: bool,
+    pub is_generator: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassDeclaration {
+    pub name: String,
+    pub superclass: Option<String>,
+    pub methods: Vec<MethodDefinition>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodDefinition {
+    pub name: String,
+    pub is_static: bool,
+    /// The method as a function named after it.
+    pub function: FunctionStatement,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct VariableExpression {
     pub lhs: Box<ASTNode>,
     pub rhs: Box<ASTNode>,
-    pub start: Line,
-    pub end: Line,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -45,8 +93,9 @@ pub struct Property {
 #[derive(Debug, Clone)]
 pub struct ObjectPattern {
     pub properties: Vec<Property>,
-    pub start: Line,
-    pub end: Line,
+    /// The name `...rest` collects the remaining properties under.
+    pub rest: Option<String>,
+    pub span: Span,
 }
 
 impl ObjectPattern {
@@ -60,71 +109,274 @@ impl ObjectPattern {
     }
 }
 
+/// An object literal. Its properties are `ObjectProperty` or `SpreadElement` nodes.
+#[derive(Debug, Clone)]
+pub struct ObjectExpression {
+    pub properties: Vec<ASTNode>,
+    pub span: Span,
+}
+
+impl ObjectExpression {
+    pub fn get_property(&self, key: &str) -> Option<&ObjectProperty> {
+        self.properties.iter().find_map(|prop| match prop {
+            ASTNode::ObjectProperty(op) => match &op.key {
+                PropertyKey::Name(name) if name == key => Some(op),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PropertyKey {
+    /// An identifier, string or number key.
+    Name(String),
+    /// A `[key]` computed from an expression.
+    Computed(Box<ASTNode>),
+}
+
+/// A `key: value` property. Shorthand properties get an `Identifier` value and methods a
+/// `FunctionStatement` named after their key.
+#[derive(Debug, Clone)]
+pub struct ObjectProperty {
+    pub key: PropertyKey,
+    pub value: Box<ASTNode>,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone)]
 pub struct BlockStatement {
     pub body: Box<Vec<ASTNode>>,
-    pub start: Line,
-    pub end: Line,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct IfStatement {
+    /// The expressions found in the condition.
+    pub test: Vec<ASTNode>,
+    pub consequent: Box<ASTNode>,
+    pub alternate: Option<Box<ASTNode>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForStatement {
+    /// The expressions found between the parentheses, whether `init; test; update` or `x of y`.
+    pub head: Vec<ASTNode>,
+    pub body: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// A `while` or `do ... while` loop.
+#[derive(Debug, Clone)]
+pub struct WhileStatement {
+    /// The expressions found in the condition.
+    pub test: Vec<ASTNode>,
+    pub body: Box<ASTNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchStatement {
+    /// The expressions found in the parentheses after `switch`.
+    pub discriminant: Vec<ASTNode>,
+    pub cases: Vec<SwitchCase>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchCase {
+    /// `None` for the `default` case.
+    pub test: Option<Box<ASTNode>>,
+    pub body: Vec<ASTNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct TryStatement {
+    pub block: Box<ASTNode>,
+    pub handler: Option<CatchClause>,
+    pub finalizer: Option<Box<ASTNode>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct CatchClause {
+    pub param: Option<Box<ASTNode>>,
+    pub body: Box<ASTNode>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct CallExpression {
     pub base: Box<ASTNode>,
-    pub param: Option<String>,
-    pub start: Line,
-    pub end: Line,
+    pub arguments: Vec<ASTNode>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct Identifier {
     pub name: String,
-    pub start: Line,
-    pub end: Line,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StringLiteral {
+    pub value: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct NumericLiteral {
+    /// The literal as written in the source.
+    pub raw: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateLiteral {
+    /// The text between substitutions, always one more than `expressions`.
+    pub quasis: Vec<String>,
+    pub expressions: Vec<ASTNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct RegexLiteral {
+    pub pattern: String,
+    pub flags: String,
+    pub span: Span,
+}
+
+/// A `...` spread argument or rest parameter.
+#[derive(Debug, Clone)]
+pub struct SpreadElement {
+    pub argument: Box<ASTNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct AwaitExpression {
+    pub argument: Box<ASTNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct YieldExpression {
+    pub argument: Option<Box<ASTNode>>,
+    /// `yield*`, handing over to another generator.
+    pub delegate: bool,
+    pub span: Span,
+}
+
+/// An expression the parser skipped over without modelling.
+#[derive(Debug, Clone)]
+pub struct Unknown {
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct MemberExpression {
     pub base: Box<ASTNode>,
     pub property: String,
-    pub start: Line,
-    pub end: Line,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ComputedMemberExpression {
+    pub base: Box<ASTNode>,
+    pub property: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// A binary or logical operation, or an assignment such as `a += b`.
+#[derive(Debug, Clone)]
+pub struct BinaryExpression {
+    pub operator: String,
+    pub left: Box<ASTNode>,
+    pub right: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// A prefix operation such as `!a` or `typeof a`, or a postfix `a++` or `a--`.
+#[derive(Debug, Clone)]
+pub struct UnaryExpression {
+    pub operator: String,
+    pub prefix: bool,
+    pub argument: Box<ASTNode>,
+    pub span: Span,
+}
+
+/// `test ? consequent : alternate`
+#[derive(Debug, Clone)]
+pub struct ConditionalExpression {
+    pub test: Box<ASTNode>,
+    pub consequent: Box<ASTNode>,
+    pub alternate: Box<ASTNode>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArrayExpression {
+    /// Holes and elements the parser couldn't make sense of are kept as `Unknown`.
+    pub elements: Vec<ASTNode>,
+    pub span: Span,
 }
 
 impl MemberExpression {
-    pub fn get_base(&self) -> &Identifier {
+    /// The identifier at the start of a chain like `a.b.c`, if it starts with one.
+    pub fn get_base(&self) -> Option<&Identifier> {
         match self.base.as_ref() {
-            ASTNode::MemberExpression(me) => return me.get_base(),
-            ASTNode::Identifier(ident) => return ident,
-            _ => panic!("invalid membership expression"),
+            ASTNode::MemberExpression(me) => me.get_base(),
+            ASTNode::Identifier(ident) => Some(ident),
+            _ => None,
         }
     }
 }
 
 impl ASTNode {
-    pub fn get_start(&self) -> usize {
+    pub fn get_span(&self) -> Span {
         match self {
-            ASTNode::BlockStatement(bs) => bs.start,
-            ASTNode::CallExpression(ce) => ce.start,
-            ASTNode::Identifier(ident) => ident.start,
-            ASTNode::FunctionStatement(fs) => fs.start,
-            ASTNode::Program(p) => p.start,
-            ASTNode::VariableExpression(ve) => ve.start,
-            ASTNode::MemberExpression(me) => me.start,
-            ASTNode::ObjectPattern(op) | ASTNode::ExportStatement(op) => op.start,
+            ASTNode::BlockStatement(bs) => bs.span,
+            ASTNode::IfStatement(is) => is.span,
+            ASTNode::ForStatement(fs) => fs.span,
+            ASTNode::WhileStatement(ws) | ASTNode::DoWhileStatement(ws) => ws.span,
+            ASTNode::SwitchStatement(ss) => ss.span,
+            ASTNode::TryStatement(ts) => ts.span,
+            ASTNode::CallExpression(ce) | ASTNode::NewExpression(ce) => ce.span,
+            ASTNode::ClassDeclaration(cd) => cd.span,
+            ASTNode::ExportIdentifier(ident) => ident.span,
+            ASTNode::Identifier(ident) => ident.span,
+            ASTNode::FunctionStatement(fs) => fs.span,
+            ASTNode::Program(p) => p.span,
+            ASTNode::VariableExpression(ve) => ve.span,
+            ASTNode::MemberExpression(me) => me.span,
+            ASTNode::ComputedMemberExpression(cme) => cme.span,
+            ASTNode::BinaryExpression(be) | ASTNode::AssignmentExpression(be) => be.span,
+            ASTNode::UnaryExpression(ue) => ue.span,
+            ASTNode::ConditionalExpression(ce) => ce.span,
+            ASTNode::ArrayExpression(ae) | ASTNode::SequenceExpression(ae) => ae.span,
+            ASTNode::StringLiteral(sl) => sl.span,
+            ASTNode::NumericLiteral(nl) => nl.span,
+            ASTNode::TemplateLiteral(tl) => tl.span,
+            ASTNode::RegexLiteral(rl) => rl.span,
+            ASTNode::SpreadElement(se) => se.span,
+            ASTNode::AwaitExpression(ae) => ae.span,
+            ASTNode::YieldExpression(ye) => ye.span,
+            ASTNode::Unknown(u) => u.span,
+            ASTNode::ObjectPattern(op) | ASTNode::ExportStatement(op) => op.span,
+            ASTNode::ObjectExpression(oe) => oe.span,
+            ASTNode::ObjectProperty(op) => op.span,
         }
     }
 
+    pub fn get_start(&self) -> usize {
+        self.get_span().start.line
+    }
+
     pub fn get_end(&self) -> usize {
-        match self {
-            ASTNode::BlockStatement(bs) => bs.end,
-            ASTNode::CallExpression(ce) => ce.end,
-            ASTNode::Identifier(ident) => ident.end,
-            ASTNode::FunctionStatement(fs) => fs.end,
-            ASTNode::Program(p) => p.end,
-            ASTNode::VariableExpression(ve) => ve.end,
-            ASTNode::MemberExpression(me) => me.end,
-            ASTNode::ObjectPattern(op) | ASTNode::ExportStatement(op) => op.end,
-        }
+        self.get_span().end.line
     }
 
     pub fn find_function(&self, name: &str) -> Option<&ASTNode> {
@@ -143,6 +395,15 @@ impl ASTNode {
         None
     }
 
+    pub fn find_class(&self, name: &str) -> Option<&ClassDeclaration> {
+        match self
+            .find_node(|node| matches!(node, ASTNode::ClassDeclaration(cd) if cd.name == name))
+        {
+            Some(ASTNode::ClassDeclaration(cd)) => Some(cd),
+            _ => None,
+        }
+    }
+
     pub fn try_get_require_file(&self) -> Option<(String, &VariableExpression)> {
         let ve = match self {
             ASTNode::VariableExpression(ve) => ve,
@@ -159,17 +420,16 @@ impl ASTNode {
             _ => return None,
         }
 
-        let require_file = if let Some(param) = &ce.param {
-            param
-        } else {
-            return None;
+        let require_file = match ce.arguments.first() {
+            Some(ASTNode::StringLiteral(sl)) => &sl.value,
+            _ => return None,
         };
 
         if !require_file.starts_with("./") && !require_file.starts_with("../") {
             return None;
         }
 
-        return Some((require_file.clone(), ve));
+        Some((require_file.clone(), ve))
     }
 
     pub fn try_export_extract(&self) -> Option<(String, &ASTNode)> {
@@ -185,26 +445,27 @@ impl ASTNode {
             _ => return None,
         };
 
-        for node in prog_lines.as_ref() {
-            if pred(node) {
-                return Some(node);
-            }
-        }
-
-        return None;
+        prog_lines.iter().find(|node| pred(node))
     }
 
     pub fn find_export_statement(&self) -> Option<&ObjectPattern> {
-        if let Some(ASTNode::ExportStatement(es)) = self.find_node(|node| match node {
-            ASTNode::ExportStatement(_) => true,
-            _ => false,
-        }) {
+        if let Some(ASTNode::ExportStatement(es)) =
+            self.find_node(|node| matches!(node, ASTNode::ExportStatement(_)))
+        {
             return Some(es);
         }
 
         None
     }
 
+    /// The name exported by `module.exports = name`.
+    pub fn find_export_identifier(&self) -> Option<&Identifier> {
+        match self.find_node(|node| matches!(node, ASTNode::ExportIdentifier(_))) {
+            Some(ASTNode::ExportIdentifier(ident)) => Some(ident),
+            _ => None,
+        }
+    }
+
     pub fn find_exported_func(&self, target: &str) -> Option<&ASTNode> {
         if let Some(es) = self.find_export_statement() {
             if let Some(val) = es.get_value(target) {
@@ -14,6 +14,12 @@ pub struct SymbolTable {
     pub table: HashMap<SymbolName, Symbol>,
 }
 
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SymbolTable {
     pub fn new() -> Self {
         SymbolTable {
@@ -34,6 +40,12 @@ pub struct ProgramScope {
     scope: Vec<FileScope>,
 }
 
+impl Default for ProgramScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ProgramScope {
     pub fn new() -> Self {
         Self { scope: Vec::new() }
@@ -56,10 +68,11 @@ impl ProgramScope {
     }
 
     pub fn pop(&mut self) {
-        let len = self.current_mut().pop();
-        if len == 0 {
-            self.scope.pop();
-        }
+        self.current_mut().pop()
+    }
+
+    pub fn pop_file(&mut self) {
+        self.scope.pop();
     }
 
     pub fn find_symbol(&self, key: &str) -> Option<&Symbol> {
@@ -101,16 +114,16 @@ impl FileScope {
         };
     }
 
-    pub fn pop(&mut self) -> usize {
+    /// Leaves the innermost block, never the file's own frame.
+    pub fn pop(&mut self) {
         self.scope.last_mut().unwrap().pop();
-        if self.scope.last().unwrap().len() == 0 {
+        if self.scope.len() > 1 && self.scope.last().unwrap().is_empty() {
             self.scope.pop();
         }
-        self.scope.len()
     }
 
     pub fn insert_symbol(&mut self, key: &str, symbol: Symbol) {
-        if self.scope.last().unwrap().len() == 0 {
+        if self.scope.last().unwrap().is_empty() {
             return self.global_table.insert(key, symbol);
         }
 
@@ -1,3 +1,6 @@
+use crate::span::{line_terminator_len, LineTable, Position};
+use std::ops::Range;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     Asterisk,
@@ -9,11 +12,38 @@ pub enum TokenType {
     Equals,
     OpenBraces,
     OpenParen,
+    OpenBracket,
+    CloseBracket,
+    Semicolon,
+    /// `=>`
+    Arrow,
+    /// `...`
+    Ellipsis,
+    /// `?.`
+    OptionalChain,
+    /// Any other punctuator, such as `===`, `+=` or `!`.
+    Operator(String),
     Newline,
     Whitespace,
     ForwardSlash,
     EOF,
     String(String),
+    /// The source text of a numeric literal, e.g. `0x1F`, `1_000n` or `.5e-3`.
+    Number(String),
+    /// A template literal without substitutions, e.g. `` `text` ``.
+    Template(String),
+    /// The start of a template literal up to its first substitution, e.g. `` `text${ ``.
+    TemplateHead(String),
+    /// The text between two substitutions, e.g. `}text${`.
+    TemplateMiddle(String),
+    /// The end of a template literal after its last substitution, e.g. `` }text` ``.
+    TemplateTail(String),
+    /// A regular expression literal including its slashes and flags, e.g. `/ab+c/gi`.
+    Regex(String),
+    /// The text of a `//` comment, up to but not including the newline.
+    LineComment(String),
+    /// The text between `/*` and `*/`.
+    BlockComment(String),
     CatchAll(String),
     Identifier(String),
 }
@@ -32,10 +62,25 @@ impl std::fmt::Display for TokenType {
             TokenType::Newline => "\n",
             TokenType::OpenBraces => "{",
             TokenType::OpenParen => "(",
+            TokenType::OpenBracket => "[",
+            TokenType::CloseBracket => "]",
+            TokenType::Semicolon => ";",
+            TokenType::Arrow => "=>",
+            TokenType::Ellipsis => "...",
+            TokenType::OptionalChain => "?.",
+            TokenType::Operator(s) => s.as_str(),
             TokenType::Whitespace => " ",
             TokenType::ForwardSlash => "/",
             TokenType::Identifier(s) => return write!(f, "{}", s),
             TokenType::String(s) => return write!(f, "{}", s),
+            TokenType::Number(s) => return write!(f, "{}", s),
+            TokenType::Template(s) => return write!(f, "`{}`", s),
+            TokenType::TemplateHead(s) => return write!(f, "`{}${{", s),
+            TokenType::TemplateMiddle(s) => return write!(f, "}}{}${{", s),
+            TokenType::TemplateTail(s) => return write!(f, "}}{}`", s),
+            TokenType::Regex(s) => return write!(f, "{}", s),
+            TokenType::LineComment(s) => return write!(f, "//{}", s),
+            TokenType::BlockComment(s) => return write!(f, "/*{}*/", s),
             TokenType::CatchAll(s) => s.as_str(),
         };
 
@@ -43,47 +88,98 @@ impl std::fmt::Display for TokenType {
     }
 }
 
+impl TokenType {
+    pub fn is_comment(&self) -> bool {
+        matches!(self, TokenType::LineComment(_) | TokenType::BlockComment(_))
+    }
+}
+
+/// Punctuators made of more than one character, or that share a first character with one,
+/// longest first so the first match is the right one.
+const PUNCTUATORS: [&str; 49] = [
+    ">>>=", "...", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "=>", "==", "!=",
+    "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "%=", "&=", "|=", "^=", "**",
+    "<<", ">>", "+", "-", "%", "<", ">", "!", "~", "^", "&", "|", "?", ";", "[", "]", "=", "*",
+    ".",
+];
+
+/// A problem found while reading a token, such as a string missing its closing quote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub position: Position,
+    pub expected: String,
+    pub found: String,
+}
+
 pub fn bytes_to_string(bytes: Vec<u8>) -> String {
     String::from_utf8_lossy(&bytes).to_string()
 }
 
-fn is_whitespace(byte: u8) -> bool {
-    match byte {
-        b' ' | b'\t' | b'\r' => true,
-        _ => false,
-    }
+fn is_whitespace(c: char) -> bool {
+    !matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}') && c.is_whitespace()
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c == '$' || c == '_' || unicode_ident::is_xid_start(c)
+}
+
+fn is_identifier_continue(c: char) -> bool {
+    c == '$' || c == '\u{200c}' || c == '\u{200d}' || unicode_ident::is_xid_continue(c)
 }
 
 #[derive(Clone)]
 pub struct Cursor {
     pos: usize,
-    pub line_num: usize,
+    token_start: Position,
+    /// The last token that wasn't whitespace or a newline.
     prev: TokenType,
+    /// The number of open braces in each template substitution being lexed, innermost last.
+    template_braces: Vec<usize>,
 }
 
 impl Default for Cursor {
     fn default() -> Self {
         Self {
             pos: 0,
-            line_num: 0,
+            token_start: Position::default(),
             prev: TokenType::Whitespace,
+            template_braces: vec![],
         }
     }
 }
 
 pub struct Lexer {
     src: Vec<u8>,
+    lines: LineTable,
     pub cursor: Cursor,
+    pub errors: Vec<LexError>,
 }
 
+const BOM: &str = "\u{feff}";
+
 impl Lexer {
+    /// Creates a lexer for `src`, skipping a leading byte order mark. Offsets still count it.
     pub fn new(src: &str) -> Lexer {
+        let cursor = Cursor {
+            pos: if src.starts_with(BOM) { BOM.len() } else { 0 },
+            ..Cursor::default()
+        };
+
         Lexer {
             src: src.as_bytes().to_owned(),
-            cursor: Cursor::default(),
+            lines: LineTable::new(src),
+            cursor,
+            errors: vec![],
         }
     }
 
+    /// Whether a `#!` at the cursor is a shebang, which can only come first in the file.
+    fn at_shebang(&self) -> bool {
+        let before = &self.src[..self.cursor.pos];
+        (before.is_empty() || before == BOM.as_bytes())
+            && self.src[self.cursor.pos..].starts_with(b"#!")
+    }
+
     fn peak_byte(&self, distance: usize) -> Option<&u8> {
         self.src.get(self.cursor.pos + distance)
     }
@@ -92,7 +188,7 @@ impl Lexer {
         let mut bytes = vec![];
         for byte in self.src.iter().skip(self.cursor.pos + offset) {
             if pred(byte) {
-                bytes.push(byte.clone())
+                bytes.push(*byte)
             } else {
                 break;
             }
@@ -101,68 +197,423 @@ impl Lexer {
         (bytes, bytes_read)
     }
 
+    /// The character starting `distance` bytes past the cursor, if that's a valid UTF-8 sequence.
+    fn peak_char(&self, distance: usize) -> Option<char> {
+        let start = (self.cursor.pos + distance).min(self.src.len());
+        let bytes = &self.src[start..(start + 4).min(self.src.len())];
+        let valid = match std::str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap(),
+        };
+
+        valid.chars().next()
+    }
+
+    fn read_chars_while(&self, mut pred: impl FnMut(char) -> bool) -> (String, usize) {
+        let mut s = String::new();
+        while let Some(c) = self.peak_char(s.len()) {
+            if !pred(c) {
+                break;
+            }
+            s.push(c);
+        }
+        let bytes_read = s.len();
+        (s, bytes_read)
+    }
+
     fn read_identifier(&self) -> (TokenType, usize) {
-        let (bytes, bytes_read) = self.read_while(|b| b.is_ascii_alphanumeric() || *b == b'_', 0);
+        let (s, bytes_read) = self.read_chars_while(is_identifier_continue);
 
-        (TokenType::Identifier(bytes_to_string(bytes)), bytes_read)
+        (TokenType::Identifier(s), bytes_read)
     }
 
-    fn read_string(&self, quote: u8) -> (TokenType, usize) {
-        let (s_bytes, inner_bytes_read) = self.read_while(|b| *b != quote, 1);
-        let s = bytes_to_string(s_bytes);
-        let bytes_read = inner_bytes_read + 2;
+    /// Reads the hex digits of a `\\x` or `\\u` escape starting at `i`, returning the character
+    /// and the index just past it.
+    fn read_hex_escape(&self, i: usize, len: usize) -> Option<(char, usize)> {
+        let (digits, end) = match (len, self.src.get(i)) {
+            (4, Some(b'{')) => {
+                let close = i + self.src[i..].iter().position(|b| *b == b'}')?;
+                (&self.src[i + 1..close], close + 1)
+            }
+            _ => (self.src.get(i..i + len)?, i + len),
+        };
+        let code = u32::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
 
-        (TokenType::String(s), bytes_read)
+        Some((char::from_u32(code)?, end))
+    }
+
+    /// Reads a quoted string, returning its value with escape sequences applied. A string that
+    /// isn't closed before the end of the line is recorded as an error.
+    fn read_string(&mut self, quote: u8) -> (TokenType, usize) {
+        let mut bytes = vec![];
+        let mut i = self.cursor.pos + 1;
+
+        let terminated = loop {
+            let byte = match self.src.get(i) {
+                None | Some(b'\n' | b'\r') => break false,
+                Some(b) if *b == quote => break true,
+                Some(b) => *b,
+            };
+            i += 1;
+
+            if byte != b'\\' {
+                bytes.push(byte);
+                continue;
+            }
+
+            let terminator = line_terminator_len(&self.src[i..]);
+            if terminator > 0 {
+                i += terminator;
+                continue;
+            }
+
+            let escaped = match self.src.get(i) {
+                Some(b) => *b,
+                None => continue,
+            };
+            i += 1;
+
+            let cooked = match escaped {
+                b'n' => '\n',
+                b't' => '\t',
+                b'r' => '\r',
+                b'b' => '\u{8}',
+                b'f' => '\u{c}',
+                b'v' => '\u{b}',
+                b'0' => '\0',
+                b'x' | b'u' => {
+                    let len = if escaped == b'x' { 2 } else { 4 };
+                    match self.read_hex_escape(i, len) {
+                        Some((c, end)) => {
+                            i = end;
+                            c
+                        }
+                        None => escaped as char,
+                    }
+                }
+                _ => {
+                    bytes.push(escaped);
+                    continue;
+                }
+            };
+            bytes.extend(cooked.to_string().as_bytes());
+        };
+
+        if !terminated {
+            let found = match self.src.get(i) {
+                None => "end of file",
+                _ => "newline",
+            };
+            self.errors.push(LexError {
+                position: self.position_at(i),
+                expected: format!("'{}'", quote as char),
+                found: found.to_owned(),
+            });
+        }
+
+        let end = if terminated { i + 1 } else { i };
+        (
+            TokenType::String(bytes_to_string(bytes)),
+            end - self.cursor.pos,
+        )
+    }
+
+    /// Reads template text up to the closing backtick or the next substitution. `head` is set when
+    /// starting at the opening backtick rather than at the `}` ending a substitution.
+    fn read_template(&self, head: bool) -> (TokenType, usize) {
+        let mut bytes = vec![];
+        let mut i = self.cursor.pos + 1;
+
+        let substitution = loop {
+            match self.src.get(i) {
+                None => break false,
+                Some(b'`') => {
+                    i += 1;
+                    break false;
+                }
+                Some(b'$') if self.src.get(i + 1) == Some(&b'{') => {
+                    i += 2;
+                    break true;
+                }
+                Some(b'\\') => {
+                    bytes.extend(self.src.get(i..(i + 2).min(self.src.len())).unwrap());
+                    i += 2;
+                }
+                Some(b) => {
+                    bytes.push(*b);
+                    i += 1;
+                }
+            }
+        };
+
+        let text = bytes_to_string(bytes);
+        let token = match (head, substitution) {
+            (true, false) => TokenType::Template(text),
+            (true, true) => TokenType::TemplateHead(text),
+            (false, true) => TokenType::TemplateMiddle(text),
+            (false, false) => TokenType::TemplateTail(text),
+        };
+
+        (token, i.min(self.src.len()) - self.cursor.pos)
+    }
+
+    /// Reads a `//` comment, or a shebang which is treated the same.
+    fn read_line_comment(&self) -> (TokenType, usize) {
+        let start = self.cursor.pos + 2;
+        let end = (start..self.src.len())
+            .find(|i| line_terminator_len(&self.src[*i..]) > 0)
+            .unwrap_or(self.src.len());
+        let text = bytes_to_string(self.src[start.min(end)..end].to_vec());
+
+        (TokenType::LineComment(text), end - self.cursor.pos)
+    }
+
+    /// Reads a block comment, running to the end of the source if it's never closed.
+    fn read_block_comment(&self) -> (TokenType, usize) {
+        let start = self.cursor.pos + 2;
+        let (text_end, end) = match self.src[start.min(self.src.len())..]
+            .windows(2)
+            .position(|w| w == b"*/")
+        {
+            Some(i) => (start + i, start + i + 2),
+            None => (self.src.len(), self.src.len()),
+        };
+        let text = bytes_to_string(self.src[start.min(text_end)..text_end].to_vec());
+
+        (TokenType::BlockComment(text), end - self.cursor.pos)
+    }
+
+    /// Whether a `/` starts a regular expression rather than being a division, judging by the
+    /// token before it.
+    fn regex_allowed(&self) -> bool {
+        match &self.cursor.prev {
+            TokenType::Identifier(ident) => matches!(
+                ident.as_str(),
+                "return"
+                    | "typeof"
+                    | "instanceof"
+                    | "in"
+                    | "of"
+                    | "new"
+                    | "delete"
+                    | "void"
+                    | "throw"
+                    | "case"
+                    | "do"
+                    | "else"
+                    | "yield"
+                    | "await"
+            ),
+            TokenType::Operator(op) => !matches!(op.as_str(), "++" | "--"),
+            TokenType::CloseParen
+            | TokenType::Number(_)
+            | TokenType::CloseBracket
+            | TokenType::String(_)
+            | TokenType::Template(_)
+            | TokenType::TemplateTail(_)
+            | TokenType::Regex(_) => false,
+            _ => true,
+        }
+    }
+
+    /// Reads a regular expression literal, falling back to a single `/` if it isn't terminated
+    /// on the same line.
+    fn read_regex(&self) -> (TokenType, usize) {
+        let mut i = self.cursor.pos + 1;
+        let mut in_class = false;
+
+        loop {
+            match self.src.get(i) {
+                None => return (TokenType::ForwardSlash, 1),
+                _ if line_terminator_len(&self.src[i..]) > 0 => {
+                    return (TokenType::ForwardSlash, 1)
+                }
+                Some(b'\\') => i += 1,
+                Some(b'[') => in_class = true,
+                Some(b']') => in_class = false,
+                Some(b'/') if !in_class => break,
+                _ => (),
+            }
+            i += 1;
+        }
+
+        let (_, flags_read) = self.read_while(|b| b.is_ascii_alphabetic(), i + 1 - self.cursor.pos);
+        let bytes_read = i + 1 + flags_read - self.cursor.pos;
+        let literal =
+            bytes_to_string(self.src[self.cursor.pos..self.cursor.pos + bytes_read].to_vec());
+
+        (TokenType::Regex(literal), bytes_read)
     }
 
     fn read_whitespace(&self) -> (TokenType, usize) {
-        let (_, bytes_read) = self.read_while(|b| is_whitespace(*b), 0);
+        let (_, bytes_read) = self.read_chars_while(is_whitespace);
         (TokenType::Whitespace, bytes_read)
     }
 
-    fn read_catch_all(&self, byte: u8) -> (TokenType, usize) {
-        let s = match String::from_utf8(vec![byte]) {
-            Ok(s) => s,
-            Err(_) => panic!("invalid character {}", byte as char),
+    /// Reads a single character the lexer has no token for. A byte that isn't valid UTF-8 is
+    /// read on its own.
+    fn read_catch_all(&self) -> (TokenType, usize) {
+        match self.peak_char(0) {
+            Some(c) => (TokenType::CatchAll(c.to_string()), c.len_utf8()),
+            None => (
+                TokenType::CatchAll(bytes_to_string(vec![self.src[self.cursor.pos]])),
+                1,
+            ),
+        }
+    }
+
+    fn read_number(&self) -> (TokenType, usize) {
+        let rest = &self.src[self.cursor.pos..];
+        let digits = |offset| {
+            self.read_while(|b| b.is_ascii_digit() || *b == b'_', offset)
+                .1
+        };
+
+        let mut len = match rest {
+            [b'0', b'x' | b'X' | b'o' | b'O' | b'b' | b'B', ..] => {
+                2 + self
+                    .read_while(|b| b.is_ascii_hexdigit() || *b == b'_', 2)
+                    .1
+            }
+            _ => {
+                let mut len = digits(0);
+                if rest.get(len) == Some(&b'.') {
+                    len += 1 + digits(len + 1);
+                }
+                if matches!(rest.get(len), Some(b'e' | b'E')) {
+                    let sign = matches!(rest.get(len + 1), Some(b'+' | b'-')) as usize;
+                    if rest.get(len + 1 + sign).is_some_and(|b| b.is_ascii_digit()) {
+                        len += 1 + sign + digits(len + 1 + sign);
+                    }
+                }
+                len
+            }
         };
 
-        (TokenType::CatchAll(s), 1)
+        if rest.get(len) == Some(&b'n') {
+            len += 1;
+        }
+
+        (
+            TokenType::Number(bytes_to_string(rest[..len].to_vec())),
+            len,
+        )
     }
 
-    fn peak(&self) -> (TokenType, usize) {
+    fn read_punctuator(&self) -> Option<(TokenType, usize)> {
+        let rest = &self.src[self.cursor.pos..];
+        let punctuator = *PUNCTUATORS
+            .iter()
+            .find(|p| rest.starts_with(p.as_bytes()))?;
+
+        let token = match punctuator {
+            // `a?.5:b` is a conditional followed by a number
+            "?." if rest.get(2).is_some_and(|b| b.is_ascii_digit()) => {
+                return Some((TokenType::Operator(String::from("?")), 1))
+            }
+            "?." => TokenType::OptionalChain,
+            "=>" => TokenType::Arrow,
+            "..." => TokenType::Ellipsis,
+            "[" => TokenType::OpenBracket,
+            "]" => TokenType::CloseBracket,
+            ";" => TokenType::Semicolon,
+            "=" => TokenType::Equals,
+            "*" => TokenType::Asterisk,
+            "." => TokenType::Dot,
+            _ => TokenType::Operator(punctuator.to_owned()),
+        };
+
+        Some((token, punctuator.len()))
+    }
+
+    fn peak(&mut self) -> (TokenType, usize) {
         let byte = match self.peak_byte(0) {
             Some(b) => b,
             None => return (TokenType::EOF, 0),
         };
 
+        let fraction = *byte == b'.' && self.peak_byte(1).is_some_and(|b| b.is_ascii_digit());
+        if byte.is_ascii_digit() || fraction {
+            return self.read_number();
+        }
+
+        let terminator = line_terminator_len(&self.src[self.cursor.pos..]);
+        if terminator > 0 {
+            return (TokenType::Newline, terminator);
+        }
+
+        if let Some(punctuator) = self.read_punctuator() {
+            return punctuator;
+        }
+
         match byte {
-            b'*' => (TokenType::Asterisk, 1),
+            b'/' if self.peak_byte(1) == Some(&b'/') => self.read_line_comment(),
+            b'#' if self.at_shebang() => self.read_line_comment(),
+            b'/' if self.peak_byte(1) == Some(&b'*') => self.read_block_comment(),
+            b'/' if self.regex_allowed() => self.read_regex(),
+            b'/' if self.peak_byte(1) == Some(&b'=') => {
+                (TokenType::Operator(String::from("/=")), 2)
+            }
             b'/' => (TokenType::ForwardSlash, 1),
             b',' => (TokenType::Comma, 1),
             b':' => (TokenType::Colon, 1),
-            b'.' => (TokenType::Dot, 1),
             b'(' => (TokenType::OpenParen, 1),
             b')' => (TokenType::CloseParen, 1),
             b'{' => (TokenType::OpenBraces, 1),
+            b'}' if self.cursor.template_braces.last() == Some(&0) => self.read_template(false),
             b'}' => (TokenType::CloseBraces, 1),
-            b'=' => (TokenType::Equals, 1),
-            b'\n' => (TokenType::Newline, 1),
+            b'`' => self.read_template(true),
             b if *b == b'"' || *b == b'\'' => self.read_string(*b),
-            b if is_whitespace(*b) => self.read_whitespace(),
-            b if b.is_ascii_alphabetic() => self.read_identifier(),
-            _ => self.read_catch_all(*byte),
+            _ => match self.peak_char(0) {
+                Some(c) if is_whitespace(c) => self.read_whitespace(),
+                Some(c) if is_identifier_start(c) => self.read_identifier(),
+                _ => self.read_catch_all(),
+            },
         }
     }
 
-    pub fn next_token(&mut self) -> TokenType {
-        loop {
-            if self.cursor.prev == TokenType::Newline {
-                self.cursor.line_num += 1;
+    fn position(&self) -> Position {
+        self.position_at(self.cursor.pos)
+    }
+
+    fn position_at(&self, pos: usize) -> Position {
+        self.lines.position(pos.min(self.src.len()))
+    }
+
+    fn advance(&mut self, bytes_read: usize) {
+        self.cursor.pos += bytes_read;
+    }
+
+    fn track_template_braces(&mut self, token: &TokenType) {
+        let braces = &mut self.cursor.template_braces;
+        match token {
+            TokenType::TemplateHead(_) => braces.push(0),
+            TokenType::TemplateTail(_) => {
+                braces.pop();
+            }
+            TokenType::OpenBraces => {
+                if let Some(open) = braces.last_mut() {
+                    *open += 1;
+                }
+            }
+            TokenType::CloseBraces => {
+                if let Some(open) = braces.last_mut() {
+                    *open -= 1;
+                }
             }
+            _ => (),
+        }
+    }
 
+    pub fn next_token(&mut self) -> TokenType {
+        loop {
             let (token, bytes_read) = self.peak();
-            self.cursor.pos += bytes_read;
-            self.cursor.prev = token.clone();
+            self.cursor.token_start = self.position();
+            self.advance(bytes_read);
+            self.track_template_braces(&token);
+            if !matches!(token, TokenType::Whitespace | TokenType::Newline) && !token.is_comment() {
+                self.cursor.prev = token.clone();
+            }
 
             if token != TokenType::Whitespace {
                 return token;
@@ -170,19 +621,48 @@ impl Lexer {
         }
     }
 
+    /// The byte range of the last token returned by `next_token`.
+    pub fn token_range(&self) -> Range<usize> {
+        self.cursor.token_start.offset..self.cursor.pos.min(self.src.len())
+    }
+
+    /// Where the last token returned by `next_token` starts.
+    pub fn token_start(&self) -> Position {
+        self.cursor.token_start
+    }
+
+    /// Where the last token returned by `next_token` ends.
+    pub fn token_end(&self) -> Position {
+        self.position()
+    }
+
+    /// Runs `f` and then puts the lexer back where it was, for looking an unknown number of
+    /// tokens ahead.
+    pub fn speculate<T>(&mut self, f: impl FnOnce(&mut Lexer) -> T) -> T {
+        let cursor_snapshot = self.cursor.clone();
+        let errors = self.errors.len();
+        let result = f(self);
+        self.cursor = cursor_snapshot;
+        self.errors.truncate(errors);
+
+        result
+    }
+
     pub fn lookahead(&mut self, distance: usize) -> TokenType {
         let mut i = distance as u32;
         let cursor_snapshot = self.cursor.clone();
+        let errors = self.errors.len();
 
         loop {
             let token = self.next_token();
-            if token == TokenType::Whitespace {
+            if token.is_comment() {
                 continue;
             }
 
             i -= 1;
-            if i <= 0 || token == TokenType::EOF {
+            if i == 0 || token == TokenType::EOF {
                 self.cursor = cursor_snapshot;
+                self.errors.truncate(errors);
                 return token;
             }
         }
@@ -2,6 +2,9 @@ pub mod ast;
 pub mod file_scope;
 pub mod lexer;
 pub mod logger;
+pub mod matcher;
 pub mod parser;
+pub mod printer;
+pub mod span;
 pub mod utils;
 pub mod visitor;
@@ -1,25 +1,39 @@
-const ESCAPE: &'static str = "\x1b[0m";
+use std::io::{self, IsTerminal};
 
-fn red(msg: &str) -> String {
+const ESCAPE: &str = "\x1b[0m";
+
+pub fn red(msg: &str) -> String {
     format!("\x1b[31m{}{}", msg, ESCAPE)
 }
 
-fn green(msg: &str) -> String {
+pub fn green(msg: &str) -> String {
     format!("\x1b[32m{}{}", msg, ESCAPE)
 }
 
-fn yellow(msg: &str) -> String {
+pub fn yellow(msg: &str) -> String {
     format!("\x1b[33m{}{}", msg, ESCAPE)
 }
 
+pub fn magenta(msg: &str) -> String {
+    format!("\x1b[35m{}{}", msg, ESCAPE)
+}
+
+fn paint(colour: fn(&str) -> String, msg: &str, is_terminal: bool) -> String {
+    if is_terminal {
+        colour(msg)
+    } else {
+        msg.to_owned()
+    }
+}
+
 pub fn err(msg: &str) {
-    eprintln!("{}", red(msg));
+    eprintln!("{}", paint(red, msg, io::stderr().is_terminal()));
 }
 
 pub fn warn(msg: &str) {
-    println!("{}", yellow(msg));
+    eprintln!("{}", paint(yellow, msg, io::stderr().is_terminal()));
 }
 
 pub fn info(msg: &str) {
-    println!("{}", green(msg));
+    println!("{}", paint(green, msg, io::stdout().is_terminal()));
 }
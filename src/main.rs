@@ -1,19 +1,11 @@
-use cjsgrep::visitor::ASTVisitor;
-use clap::{command, Arg};
-use std::env;
-
-fn parse_file(filename: &str, pattern: &str, func_start: Option<&str>) {
-    // let src = match fs::read_to_string(filename) {
-    //     Ok(s) => s,
-    //     Err(err) => {
-    //         eprintln!("failed to read file: {}", err.to_string());
-    //         process::exit(1);
-    //     }
-    // };
-
-    let mut visitor = ASTVisitor::new(pattern);
-    let _ = visitor.search(filename, func_start);
-}
+use cjsgrep::{
+    logger,
+    matcher::{MatchKind, Matcher, MatcherOptions},
+    printer::{Format, Printer},
+    visitor::ASTVisitor,
+};
+use clap::{command, Arg, ArgAction};
+use std::process;
 
 fn main() {
     let matches = command!()
@@ -33,11 +25,98 @@ fn main() {
                 .long("function-name")
                 .help("the starting function name"),
         )
+        .arg(
+            Arg::new("fixed-strings")
+                .short('F')
+                .long("fixed-strings")
+                .action(ArgAction::SetTrue)
+                .help("treat the pattern as a literal string"),
+        )
+        .arg(
+            Arg::new("ignore-case")
+                .short('i')
+                .long("ignore-case")
+                .action(ArgAction::SetTrue)
+                .help("search case insensitively"),
+        )
+        .arg(
+            Arg::new("word-regexp")
+                .short('w')
+                .long("word-regexp")
+                .action(ArgAction::SetTrue)
+                .help("only match whole words"),
+        )
+        .arg(
+            Arg::new("match-kind")
+                .long("match-kind")
+                .value_parser(["identifier", "string", "comment", "code", "any"])
+                .default_value("any")
+                .help("only match inside tokens of this kind"),
+        )
+        .arg(
+            Arg::new("data-flow")
+                .long("data-flow")
+                .action(ArgAction::SetTrue)
+                .help("follow the pattern through call arguments into function parameters"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["text", "json", "jsonl"])
+                .default_value("text")
+                .help("the output format"),
+        )
+        .arg(
+            Arg::new("trace")
+                .long("trace")
+                .action(ArgAction::SetTrue)
+                .help("print the call path that led to each match"),
+        )
         .get_matches();
 
     let pattern = matches.get_one::<String>("pattern").unwrap();
     let filepath = matches.get_one::<String>("filepath").unwrap();
     let func_start = matches.get_one::<String>("function");
+    let trace = matches.get_flag("trace");
+    let format: Format = matches
+        .get_one::<String>("format")
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    let options = MatcherOptions {
+        fixed_strings: matches.get_flag("fixed-strings"),
+        ignore_case: matches.get_flag("ignore-case"),
+        whole_word: matches.get_flag("word-regexp"),
+        kind: matches
+            .get_one::<String>("match-kind")
+            .unwrap()
+            .parse::<MatchKind>()
+            .unwrap(),
+    };
+
+    let matcher = match Matcher::new(pattern, &options) {
+        Ok(matcher) => matcher,
+        Err(err) => {
+            logger::err(&err);
+            process::exit(1);
+        }
+    };
+
+    let mut visitor = ASTVisitor::new(matcher).with_data_flow(matches.get_flag("data-flow"));
+    let results = visitor.search(filepath, func_start.map(|s| s.as_str()));
+
+    for warning in visitor.warnings() {
+        logger::warn(warning);
+    }
+
+    let results = match results {
+        Ok(results) => results,
+        Err(err) => {
+            logger::err(&err);
+            process::exit(1);
+        }
+    };
 
-    parse_file(filepath, pattern, func_start.map(|s| s.as_str()));
+    Printer::new(format, trace).print(&results);
 }
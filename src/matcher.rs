@@ -0,0 +1,168 @@
+use crate::lexer::{Lexer, TokenType};
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+/// The kind of token a match has to fall inside of.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MatchKind {
+    Identifier,
+    String,
+    Comment,
+    /// Anywhere outside of a comment.
+    Code,
+    #[default]
+    Any,
+}
+
+impl std::str::FromStr for MatchKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "identifier" => Ok(MatchKind::Identifier),
+            "string" => Ok(MatchKind::String),
+            "comment" => Ok(MatchKind::Comment),
+            "code" => Ok(MatchKind::Code),
+            "any" => Ok(MatchKind::Any),
+            _ => Err(format!("unknown match kind '{}'", s)),
+        }
+    }
+}
+
+/// A byte range of the source covered by a token of the given kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub kind: MatchKind,
+    pub range: Range<usize>,
+}
+
+/// Splits `src` into identifier, string and comment regions, ordered by position.
+pub fn classify(src: &str) -> Vec<Region> {
+    let mut lexer = Lexer::new(src);
+    let mut regions: Vec<Region> = vec![];
+
+    loop {
+        let token = lexer.next_token();
+        let range = lexer.token_range();
+        if token == TokenType::EOF {
+            break;
+        }
+
+        match token {
+            TokenType::Identifier(_) => regions.push(Region {
+                kind: MatchKind::Identifier,
+                range,
+            }),
+            TokenType::String(_)
+            | TokenType::Regex(_)
+            | TokenType::Template(_)
+            | TokenType::TemplateHead(_)
+            | TokenType::TemplateMiddle(_)
+            | TokenType::TemplateTail(_) => regions.push(Region {
+                kind: MatchKind::String,
+                range,
+            }),
+            TokenType::LineComment(_) | TokenType::BlockComment(_) => regions.push(Region {
+                kind: MatchKind::Comment,
+                range,
+            }),
+            _ => (),
+        }
+    }
+
+    regions
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MatcherOptions {
+    /// Treat the pattern as a literal string rather than a regular expression.
+    pub fixed_strings: bool,
+    pub ignore_case: bool,
+    /// Only match the pattern at word boundaries.
+    pub whole_word: bool,
+    pub kind: MatchKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    regex: Regex,
+    kind: MatchKind,
+}
+
+impl Matcher {
+    pub fn new(pattern: &str, options: &MatcherOptions) -> Result<Matcher, String> {
+        let mut pattern = if options.fixed_strings {
+            regex::escape(pattern)
+        } else {
+            pattern.to_owned()
+        };
+
+        if options.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+
+        match RegexBuilder::new(&pattern)
+            .case_insensitive(options.ignore_case)
+            .build()
+        {
+            Ok(regex) => Ok(Matcher {
+                regex,
+                kind: options.kind,
+            }),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Matches any of the given identifier names as a whole word.
+    pub fn identifiers(names: &[String]) -> Matcher {
+        let alternation: Vec<String> = names.iter().map(|n| regex::escape(n)).collect();
+        let options = MatcherOptions {
+            whole_word: true,
+            kind: MatchKind::Identifier,
+            ..Default::default()
+        };
+
+        Matcher::new(&alternation.join("|"), &options).unwrap()
+    }
+
+    pub fn kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    /// Returns the byte offset of the first match in `haystack`.
+    pub fn find(&self, haystack: &str) -> Option<usize> {
+        self.regex.find(haystack).map(|m| m.start())
+    }
+
+    /// Whether the pattern matches the whole of `s`.
+    pub fn is_match_whole(&self, s: &str) -> bool {
+        self.regex
+            .find_iter(s)
+            .any(|m| m.start() == 0 && m.end() == s.len())
+    }
+
+    /// Returns the byte offset of the first match in `line` that sits inside a region of the
+    /// matcher's kind. `offset` is the position of `line` in the source `regions` describe.
+    pub fn find_in(&self, line: &str, offset: usize, regions: &[Region]) -> Option<usize> {
+        if self.kind == MatchKind::Any {
+            return self.find(line);
+        }
+
+        self.regex
+            .find_iter(line)
+            .map(|m| (offset + m.start())..(offset + m.end()))
+            .find(|range| {
+                let i = regions.partition_point(|r| r.range.end <= range.start);
+                match self.kind {
+                    MatchKind::Code => regions[i..]
+                        .iter()
+                        .take_while(|r| r.range.start < range.end)
+                        .all(|r| r.kind != MatchKind::Comment),
+                    kind => regions.get(i).is_some_and(|r| {
+                        r.kind == kind && r.range.start <= range.start && range.end <= r.range.end
+                    }),
+                }
+            })
+            .map(|range| range.start - offset)
+    }
+}
@@ -1,30 +1,105 @@
 use crate::{
     ast::{
-        self, ASTNode, BlockStatement, CallExpression, FunctionStatement, Identifier,
-        MemberExpression, ObjectPattern, Program, VariableExpression,
+        self, ASTNode, ArrayExpression, AwaitExpression, BinaryExpression, BlockStatement,
+        CallExpression, CatchClause, ClassDeclaration, ComputedMemberExpression,
+        ConditionalExpression, ForStatement, FunctionStatement, Identifier, IfStatement,
+        MemberExpression, MethodDefinition, NumericLiteral, ObjectExpression, ObjectPattern,
+        ObjectProperty, Position, Program, PropertyKey, RegexLiteral, Span, SpreadElement,
+        StringLiteral, SwitchCase, SwitchStatement, TemplateLiteral, TryStatement, UnaryExpression,
+        Unknown, VariableExpression, WhileStatement, YieldExpression,
     },
-    lexer::{self, TokenType},
+    lexer::{self, Lexer, TokenType},
 };
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub file: Option<String>,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based byte column.
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    pub fn with_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_owned());
+        self
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+
+        write!(
+            f,
+            "{}:{}: expected {}, found {}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+fn describe(token: &TokenType) -> String {
+    match token {
+        TokenType::EOF => String::from("end of file"),
+        TokenType::Newline => String::from("newline"),
+        _ => format!("'{}'", token),
+    }
+}
+
 pub struct Parser {
     lexer: lexer::Lexer,
     curr_token: TokenType,
-}
-
-fn exit(message: &str) -> ! {
-    eprintln!("{}", message);
-    std::process::exit(1)
+    /// Where the last consumed token ends.
+    prev_end: Position,
+    errors: Vec<ParseError>,
 }
 
 impl Parser {
     pub fn new(src: &str) -> Parser {
-        let mut lexer = lexer::Lexer::new(src);
-        let curr_token = lexer.next_token();
-        Parser { lexer, curr_token }
+        let mut parser = Parser {
+            lexer: lexer::Lexer::new(src),
+            curr_token: TokenType::EOF,
+            prev_end: Position::default(),
+            errors: vec![],
+        };
+        parser.advance_token();
+        parser
     }
 
-    pub fn parse(&mut self) -> ASTNode {
-        self.program()
+    /// Parses the source, failing on the first syntax error.
+    pub fn parse(&mut self) -> ParseResult<ASTNode> {
+        let (ast, mut errors) = self.parse_recovering();
+        match errors.is_empty() {
+            true => Ok(ast),
+            false => Err(errors.remove(0)),
+        }
+    }
+
+    /// Parses the source, skipping statements with syntax errors. Returns the partial AST along
+    /// with every error that was recovered from.
+    pub fn parse_recovering(&mut self) -> (ASTNode, Vec<ParseError>) {
+        let ast = self.program();
+        let lex_errors = std::mem::take(&mut self.lexer.errors);
+        self.errors
+            .extend(lex_errors.into_iter().map(|e| ParseError {
+                file: None,
+                line: e.position.line + 1,
+                column: e.position.column + 1,
+                expected: e.expected,
+                found: e.found,
+            }));
+        self.errors.sort_by_key(|e| (e.line, e.column));
+
+        (ast, std::mem::take(&mut self.errors))
     }
 
     // fn lookahead(&mut self, distance: usize) -> TokenType {
@@ -34,22 +109,508 @@ impl Parser {
     //     }
     // }
 
+    /// Moves to the next token, skipping over comments.
     fn advance_token(&mut self) {
-        self.curr_token = self.lexer.next_token();
+        self.prev_end = self.lexer.token_end();
+        loop {
+            self.curr_token = self.lexer.next_token();
+            if !self.curr_token.is_comment() {
+                break;
+            }
+        }
+    }
+
+    fn curr_span(&self) -> Span {
+        Span::new(self.lexer.token_start(), self.lexer.token_end())
     }
 
     fn advance_token_till(&mut self, pred: impl Fn(&TokenType) -> bool) {
+        while !pred(&self.curr_token) && self.curr_token != TokenType::EOF {
+            self.advance_token();
+        }
+    }
+
+    fn error(&self, expected: &str) -> ParseError {
+        ParseError {
+            file: None,
+            line: self.lexer.token_start().line + 1,
+            column: self.lexer.token_start().column + 1,
+            expected: expected.to_owned(),
+            found: describe(&self.curr_token),
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.curr_token == TokenType::Newline {
+            self.advance_token();
+        }
+    }
+
+    /// Skips to the next `,` or closing bracket that isn't nested inside the current element.
+    fn skip_list_element(&mut self) {
+        let mut depth = 0;
         loop {
-            if pred(&self.curr_token) {
+            match &self.curr_token {
+                TokenType::EOF => return,
+                TokenType::Comma
+                | TokenType::CloseParen
+                | TokenType::CloseBraces
+                | TokenType::TemplateMiddle(_)
+                | TokenType::TemplateTail(_)
+                    if depth == 0 =>
+                {
+                    return
+                }
+                TokenType::CloseBracket if depth == 0 => return,
+                TokenType::OpenParen
+                | TokenType::OpenBraces
+                | TokenType::OpenBracket
+                | TokenType::TemplateHead(_) => depth += 1,
+                TokenType::CloseParen
+                | TokenType::CloseBraces
+                | TokenType::CloseBracket
+                | TokenType::TemplateTail(_) => depth -= 1,
+                _ => (),
+            }
+            self.advance_token();
+        }
+    }
+
+    /// Parses a parenthesised, comma separated list. Elements that `element` can't make sense of
+    /// are skipped and kept as `Unknown` so the remaining elements keep their position.
+    fn parenthesized_list(
+        &mut self,
+        element: fn(&mut Self) -> ParseResult<Option<ASTNode>>,
+    ) -> ParseResult<Vec<ASTNode>> {
+        self.delimited_list(TokenType::OpenParen, TokenType::CloseParen, element)
+    }
+
+    fn delimited_list(
+        &mut self,
+        open: TokenType,
+        close: TokenType,
+        element: fn(&mut Self) -> ParseResult<Option<ASTNode>>,
+    ) -> ParseResult<Vec<ASTNode>> {
+        let mut elements = vec![];
+        self.eat(&open)?;
+
+        loop {
+            self.skip_newlines();
+            if self.curr_token == close || self.curr_token == TokenType::EOF {
+                break;
+            }
+
+            let start = self.lexer.token_start();
+            let mut node = element(self)?;
+            self.skip_newlines();
+            if self.curr_token != TokenType::Comma && self.curr_token != close {
+                node = None;
+                self.skip_list_element();
+            }
+
+            elements.push(node.unwrap_or(ASTNode::Unknown(Unknown {
+                span: Span::new(start, self.prev_end),
+            })));
+
+            if self.curr_token != TokenType::Comma {
                 break;
             }
             self.advance_token();
         }
+
+        self.eat(&close)?;
+        Ok(elements)
+    }
+
+    fn param(&mut self) -> ParseResult<Option<ASTNode>> {
+        match &self.curr_token {
+            TokenType::Identifier(ident) => {
+                let node = ASTNode::Identifier(Identifier {
+                    name: ident.clone(),
+                    span: self.curr_span(),
+                });
+                self.advance_token();
+                Ok(Some(node))
+            }
+            TokenType::OpenBraces => Ok(Some(ASTNode::ObjectPattern(
+                self.object_pattern_expression()?,
+            ))),
+            TokenType::Ellipsis => self.spread_element(Self::param),
+            _ => Ok(None),
+        }
+    }
+
+    fn spread_element(
+        &mut self,
+        argument: fn(&mut Self) -> ParseResult<Option<ASTNode>>,
+    ) -> ParseResult<Option<ASTNode>> {
+        let start = self.lexer.token_start();
+        self.eat(&TokenType::Ellipsis)?;
+
+        Ok(argument(self)?.map(|argument| {
+            ASTNode::SpreadElement(SpreadElement {
+                argument: Box::new(argument),
+                span: Span::new(start, self.prev_end),
+            })
+        }))
+    }
+
+    /// Whether the `(` at the cursor opens the parameters of an arrow function rather than a
+    /// parenthesized expression.
+    fn at_arrow_params(&mut self) -> bool {
+        self.lexer.speculate(closes_arrow_params)
+    }
+
+    /// Whether `async` at the cursor starts an async function rather than being a plain
+    /// identifier.
+    fn at_async_function(&mut self) -> bool {
+        match self.lexer.lookahead(1) {
+            TokenType::Identifier(ident) if ident == "function" => true,
+            TokenType::Identifier(_) => self.lexer.lookahead(2) == TokenType::Arrow,
+            TokenType::OpenParen => self.lexer.speculate(|lexer| {
+                lexer.next_token() == TokenType::OpenParen && closes_arrow_params(lexer)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Whether `await` or `yield` at the cursor is an operator. Outside of async functions and
+    /// generators they're plain identifiers, e.g. `await = 1`.
+    fn at_operator_keyword(&mut self, keyword: &str) -> bool {
+        match self.lexer.lookahead(1) {
+            TokenType::Equals
+            | TokenType::Dot
+            | TokenType::OptionalChain
+            | TokenType::Arrow
+            | TokenType::Operator(_) => false,
+            // unlike `yield`, `await` needs an operand
+            TokenType::Asterisk
+            | TokenType::Comma
+            | TokenType::Colon
+            | TokenType::Semicolon
+            | TokenType::Newline
+            | TokenType::CloseParen
+            | TokenType::CloseBracket
+            | TokenType::CloseBraces
+            | TokenType::EOF => keyword == "yield",
+            _ => true,
+        }
+    }
+
+    /// Parses `(expression)`, or `(a, b)` as a sequence.
+    fn parenthesized_expression(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        let mut expressions = self.parenthesized_list(Self::expression)?;
+        let span = Span::new(start, self.prev_end);
+
+        Ok(match expressions.len() {
+            0 => ASTNode::Unknown(Unknown { span }),
+            1 => expressions.pop().unwrap(),
+            _ => ASTNode::SequenceExpression(ArrayExpression {
+                elements: expressions,
+                span,
+            }),
+        })
+    }
+
+    /// Skips the newlines before a token that carries on the expression from the previous line,
+    /// e.g. a `.then()` or `||` starting the next line.
+    fn continue_on_next_line(&mut self) {
+        if self.curr_token != TokenType::Newline {
+            return;
+        }
+
+        let continues = self.lexer.speculate(|lexer| loop {
+            let token = lexer.next_token();
+            if token == TokenType::Newline || token.is_comment() {
+                continue;
+            }
+            break match &token {
+                TokenType::Dot | TokenType::OptionalChain => true,
+                TokenType::Operator(op) if op == "?" => true,
+                TokenType::Identifier(_) => false,
+                _ => binary_operator(&token).is_some(),
+            };
+        });
+
+        if continues {
+            let prev_end = self.prev_end;
+            self.skip_newlines();
+            self.prev_end = prev_end;
+        }
+    }
+
+    /// Parses an assignment expression, the largest expression that can't contain a `,`
+    /// outside of brackets.
+    fn expression(&mut self) -> ParseResult<Option<ASTNode>> {
+        let arrow = match &self.curr_token {
+            TokenType::OpenParen => self.at_arrow_params(),
+            TokenType::Identifier(_) => self.lexer.lookahead(1) == TokenType::Arrow,
+            _ => false,
+        };
+        if arrow {
+            return Ok(Some(ASTNode::FunctionStatement(self.arrow_function()?)));
+        }
+
+        if let TokenType::Identifier(ident) = self.curr_token.clone() {
+            match ident.as_str() {
+                "async" if self.at_async_function() => {
+                    return Ok(Some(ASTNode::FunctionStatement(self.async_function()?)));
+                }
+                "yield" if self.at_operator_keyword(&ident) => {
+                    return Ok(Some(self.yield_expression()?));
+                }
+                _ => (),
+            }
+        }
+
+        let left = match self.conditional_expression()? {
+            Some(left) => left,
+            None => return Ok(None),
+        };
+        let operator = match &self.curr_token {
+            TokenType::Equals => String::from("="),
+            TokenType::Operator(op) if is_assignment_operator(op) => op.clone(),
+            _ => return Ok(Some(left)),
+        };
+        self.advance_token();
+        self.skip_newlines();
+        let right = self.expression()?.ok_or_else(|| self.error("expression"))?;
+
+        Ok(Some(ASTNode::AssignmentExpression(BinaryExpression {
+            operator,
+            span: Span::new(left.get_span().start, self.prev_end),
+            left: Box::new(left),
+            right: Box::new(right),
+        })))
+    }
+
+    fn conditional_expression(&mut self) -> ParseResult<Option<ASTNode>> {
+        let test = match self.binary_expression(0)? {
+            Some(test) => test,
+            None => return Ok(None),
+        };
+        self.continue_on_next_line();
+        if self.curr_token != TokenType::Operator(String::from("?")) {
+            return Ok(Some(test));
+        }
+
+        self.advance_token();
+        self.skip_newlines();
+        let consequent = self.expression()?.ok_or_else(|| self.error("expression"))?;
+        self.skip_newlines();
+        self.eat(&TokenType::Colon)?;
+        self.skip_newlines();
+        let alternate = self.expression()?.ok_or_else(|| self.error("expression"))?;
+
+        Ok(Some(ASTNode::ConditionalExpression(
+            ConditionalExpression {
+                span: Span::new(test.get_span().start, self.prev_end),
+                test: Box::new(test),
+                consequent: Box::new(consequent),
+                alternate: Box::new(alternate),
+            },
+        )))
+    }
+
+    /// Parses operators binding at least as tightly as `min_precedence`, see `binary_operator`.
+    fn binary_expression(&mut self, min_precedence: u8) -> ParseResult<Option<ASTNode>> {
+        let mut left = match self.unary_expression()? {
+            Some(left) => left,
+            None => return Ok(None),
+        };
+
+        loop {
+            self.continue_on_next_line();
+            let (operator, precedence) = match binary_operator(&self.curr_token) {
+                Some((operator, precedence)) if precedence >= min_precedence => {
+                    (operator, precedence)
+                }
+                _ => return Ok(Some(left)),
+            };
+            self.advance_token();
+            self.skip_newlines();
+
+            // `**` is right-associative
+            let right = match operator.as_str() {
+                "**" => self.binary_expression(precedence)?,
+                _ => self.binary_expression(precedence + 1)?,
+            };
+            let right = right.ok_or_else(|| self.error("expression"))?;
+
+            left = ASTNode::BinaryExpression(BinaryExpression {
+                operator,
+                span: Span::new(left.get_span().start, self.prev_end),
+                left: Box::new(left),
+                right: Box::new(right),
+            });
+        }
+    }
+
+    fn unary_expression(&mut self) -> ParseResult<Option<ASTNode>> {
+        let start = self.lexer.token_start();
+        let operator = match &self.curr_token {
+            TokenType::Operator(op)
+                if matches!(op.as_str(), "!" | "~" | "+" | "-" | "++" | "--") =>
+            {
+                op.clone()
+            }
+            TokenType::Identifier(op) if matches!(op.as_str(), "typeof" | "void" | "delete") => {
+                op.clone()
+            }
+            TokenType::Identifier(op) if op == "await" => match self.at_operator_keyword("await") {
+                true => return self.await_expression(),
+                false => return self.postfix_expression(),
+            },
+            _ => return self.postfix_expression(),
+        };
+        self.advance_token();
+        let argument = self
+            .unary_expression()?
+            .ok_or_else(|| self.error("expression"))?;
+
+        Ok(Some(ASTNode::UnaryExpression(UnaryExpression {
+            operator,
+            prefix: true,
+            argument: Box::new(argument),
+            span: Span::new(start, self.prev_end),
+        })))
+    }
+
+    fn postfix_expression(&mut self) -> ParseResult<Option<ASTNode>> {
+        let argument = match self.primary_expression()? {
+            Some(argument) => argument,
+            None => return Ok(None),
+        };
+        let operator = match &self.curr_token {
+            TokenType::Operator(op) if op == "++" || op == "--" => op.clone(),
+            _ => return Ok(Some(argument)),
+        };
+        self.advance_token();
+
+        Ok(Some(ASTNode::UnaryExpression(UnaryExpression {
+            operator,
+            prefix: false,
+            span: Span::new(argument.get_span().start, self.prev_end),
+            argument: Box::new(argument),
+        })))
+    }
+
+    /// Parses a literal, identifier or bracketed expression along with any member accesses and
+    /// calls that follow it.
+    fn primary_expression(&mut self) -> ParseResult<Option<ASTNode>> {
+        let node = match &self.curr_token {
+            TokenType::Ellipsis => return self.spread_element(Self::expression),
+            TokenType::OpenParen => self.parenthesized_expression()?,
+            TokenType::OpenBracket => {
+                let start = self.lexer.token_start();
+                let elements = self.delimited_list(
+                    TokenType::OpenBracket,
+                    TokenType::CloseBracket,
+                    Self::expression,
+                )?;
+                ASTNode::ArrayExpression(ArrayExpression {
+                    elements,
+                    span: Span::new(start, self.prev_end),
+                })
+            }
+            TokenType::String(s) => {
+                let node = ASTNode::StringLiteral(StringLiteral {
+                    value: s.clone(),
+                    span: self.curr_span(),
+                });
+                self.advance_token();
+                node
+            }
+            TokenType::Number(raw) => {
+                let node = ASTNode::NumericLiteral(NumericLiteral {
+                    raw: raw.clone(),
+                    span: self.curr_span(),
+                });
+                self.advance_token();
+                node
+            }
+            TokenType::OpenBraces => ASTNode::ObjectExpression(self.object_expression()?),
+            TokenType::Identifier(ident) if ident == "new" => self.new_expression()?,
+            TokenType::Identifier(ident) if ident == "function" => {
+                ASTNode::FunctionStatement(self.function_expression()?)
+            }
+            TokenType::Identifier(ident) => {
+                let node = ASTNode::Identifier(Identifier {
+                    name: ident.clone(),
+                    span: self.curr_span(),
+                });
+                self.advance_token();
+                node
+            }
+            TokenType::Template(_) | TokenType::TemplateHead(_) => self.template_literal()?,
+            TokenType::Regex(literal) => {
+                let (pattern, flags) = literal[1..].rsplit_once('/').unwrap_or_default();
+                let node = ASTNode::RegexLiteral(RegexLiteral {
+                    pattern: pattern.to_string(),
+                    flags: flags.to_string(),
+                    span: self.curr_span(),
+                });
+                self.advance_token();
+                node
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(self.member_expression(node)?))
+    }
+
+    fn template_literal(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        let mut quasis = vec![];
+        let mut expressions = vec![];
+
+        loop {
+            match self.curr_token.clone() {
+                TokenType::Template(text) | TokenType::TemplateTail(text) => {
+                    quasis.push(text);
+                    self.advance_token();
+                    break;
+                }
+                TokenType::TemplateHead(text) | TokenType::TemplateMiddle(text) => {
+                    quasis.push(text);
+                    self.advance_token();
+                    expressions.push(self.substitution()?);
+                }
+                _ => return Err(self.error("template literal")),
+            }
+        }
+
+        Ok(ASTNode::TemplateLiteral(TemplateLiteral {
+            quasis,
+            expressions,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    /// Parses the expression inside `${}`, leaving the cursor on the template text that follows.
+    fn substitution(&mut self) -> ParseResult<ASTNode> {
+        self.skip_newlines();
+        let start = self.lexer.token_start();
+        let mut node = self.expression()?;
+        self.skip_newlines();
+
+        if !matches!(
+            self.curr_token,
+            TokenType::TemplateMiddle(_) | TokenType::TemplateTail(_)
+        ) {
+            node = None;
+            self.skip_list_element();
+        }
+
+        Ok(node.unwrap_or(ASTNode::Unknown(Unknown {
+            span: Span::new(start, self.prev_end),
+        })))
     }
 
     fn advance_token_against(&mut self, target: Vec<TokenType>) -> bool {
         for t in target {
-            if &self.curr_token != &t {
+            if self.curr_token != t {
                 return false;
             }
             self.advance_token();
@@ -59,27 +620,22 @@ impl Parser {
     }
 
     fn program(&mut self) -> ASTNode {
-        let start = self.lexer.cursor.line_num;
+        let start = self.lexer.token_start();
         let statement_list = self.statement_list();
         ASTNode::Program(Program {
             lines: Box::new(statement_list),
-            start,
-            end: self.lexer.cursor.line_num,
+            span: Span::new(start, self.prev_end),
         })
     }
 
-    fn eat(&mut self, expected_token: &TokenType) -> TokenType {
-        if self.curr_token == TokenType::EOF {
-            exit(format!("EOF").as_str());
-        }
-
+    fn eat(&mut self, expected_token: &TokenType) -> ParseResult<TokenType> {
         if expected_token != &self.curr_token {
-            exit(format!("unexpected token '{}'", self.curr_token).as_str());
+            return Err(self.error(&describe(expected_token)));
         }
 
         let previous_token = self.curr_token.clone();
         self.advance_token();
-        previous_token
+        Ok(previous_token)
     }
 
     fn eat_identifier(&mut self) -> Option<String> {
@@ -97,92 +653,630 @@ impl Parser {
         let mut statements = vec![];
 
         while self.curr_token != TokenType::EOF {
-            if let Some(statement) = self.statement() {
-                statements.push(statement);
-            }
+            self.recovering_statement(&mut statements);
         }
 
         statements
     }
 
-    fn statement(&mut self) -> Option<ASTNode> {
+    fn recovering_statement(&mut self, statements: &mut Vec<ASTNode>) {
+        let result = match self.at_declaration() {
+            // each declarator of `const a = 1, b = 2` is a statement of its own
+            true => self.variable_statement(),
+            false => self.statement().map(|s| s.into_iter().collect()),
+        };
+
+        match result {
+            Ok(nodes) => statements.extend(nodes),
+            Err(e) => {
+                self.errors.push(e);
+                self.synchronize();
+            }
+        }
+    }
+
+    /// Skips the rest of a statement that failed to parse. Stops after the next newline or `;`
+    /// outside of any brackets, or before a `}` closing the enclosing block or a `function`
+    /// keyword at the start of a line.
+    fn synchronize(&mut self) {
+        let mut depth = 0;
+        let mut line_start = false;
+
+        loop {
+            match &self.curr_token {
+                TokenType::EOF => return,
+                TokenType::CloseBraces if depth == 0 => return,
+                TokenType::Identifier(ident) if ident == "function" && line_start => return,
+                TokenType::Newline if depth == 0 => {
+                    self.advance_token();
+                    return;
+                }
+                TokenType::Semicolon if depth == 0 => {
+                    self.advance_token();
+                    return;
+                }
+                TokenType::OpenParen | TokenType::OpenBraces => depth += 1,
+                TokenType::CloseParen | TokenType::CloseBraces => depth -= 1,
+                _ => (),
+            }
+
+            line_start = self.curr_token == TokenType::Newline;
+            self.advance_token();
+        }
+    }
+
+    fn statement(&mut self) -> ParseResult<Option<ASTNode>> {
         match &self.curr_token {
-            TokenType::OpenBraces => Some(self.block_statement()),
+            TokenType::OpenBraces => Ok(Some(self.block_statement()?)),
             TokenType::Identifier(ident) => match ident.as_str() {
-                "function" => self.function_expression(),
-                "const" | "var" | "let" => self.variable_statement(),
+                "function" => Ok(Some(ASTNode::FunctionStatement(
+                    self.function_expression()?,
+                ))),
+                "const" | "var" | "let" => {
+                    // a declaration list used as the body of a statement is kept together
+                    let start = self.lexer.token_start();
+                    let mut declarations = self.variable_statement()?;
+                    match declarations.len() {
+                        0 | 1 => Ok(declarations.pop()),
+                        _ => Ok(Some(ASTNode::BlockStatement(BlockStatement {
+                            body: Box::new(declarations),
+                            span: Span::new(start, self.prev_end),
+                        }))),
+                    }
+                }
                 "module" => self.export_statement(),
-                "if" => {
+                "if" => Ok(Some(self.if_statement()?)),
+                "for" => Ok(Some(self.for_statement()?)),
+                "while" => Ok(Some(self.while_statement()?)),
+                "do" => Ok(Some(self.do_while_statement()?)),
+                "switch" => Ok(Some(self.switch_statement()?)),
+                "try" => Ok(Some(self.try_statement()?)),
+                "class" => self.class_declaration(),
+                "return" | "throw" => {
                     self.advance_token();
-                    None
+                    self.expression()
                 }
-                _ => Some(self.parse_identifier(ident.to_string())),
+                _ => self.expression_statement(),
             },
-            TokenType::ForwardSlash => {
-                self.parse_backslash();
-                None
+            _ => self.expression_statement(),
+        }
+    }
+
+    /// Parses an expression used as a statement, skipping the token at the cursor if it can't
+    /// start one.
+    fn expression_statement(&mut self) -> ParseResult<Option<ASTNode>> {
+        let expression = self.expression()?;
+        if expression.is_none() {
+            self.advance_token();
+        }
+
+        Ok(expression)
+    }
+
+    /// Whether a `const`, `var` or `let` declaration starts at the cursor.
+    fn at_declaration(&self) -> bool {
+        match &self.curr_token {
+            TokenType::Identifier(ident) => matches!(ident.as_str(), "const" | "var" | "let"),
+            _ => false,
+        }
+    }
+
+    /// Whether `keyword` comes next. Newlines and `;` before it are skipped without counting
+    /// towards the end of the previous node.
+    fn at_keyword(&mut self, keyword: &str) -> bool {
+        let prev_end = self.prev_end;
+        while matches!(self.curr_token, TokenType::Newline | TokenType::Semicolon) {
+            self.advance_token();
+        }
+        self.prev_end = prev_end;
+
+        matches!(&self.curr_token, TokenType::Identifier(ident) if ident == keyword)
+    }
+
+    /// Parses the parenthesized head of a control-flow statement, keeping the expressions it can
+    /// make sense of and skipping the rest.
+    fn statement_head(&mut self) -> ParseResult<Vec<ASTNode>> {
+        let mut expressions = vec![];
+        self.eat(&TokenType::OpenParen)?;
+
+        while !matches!(self.curr_token, TokenType::CloseParen | TokenType::EOF) {
+            // `const [k, v]` would otherwise be read as a member of `const`
+            if self.at_declaration() {
+                self.advance_token();
+                self.binding_pattern()?;
+                continue;
+            }
+
+            match self.expression()? {
+                Some(expression) => expressions.push(expression),
+                None => self.advance_token(),
+            }
+        }
+
+        self.eat(&TokenType::CloseParen)?;
+        Ok(expressions)
+    }
+
+    /// Skips the name or destructuring pattern a declaration binds, e.g. `[k, v]` in
+    /// `for (const [k, v] of entries)`.
+    fn binding_pattern(&mut self) -> ParseResult<()> {
+        match self.curr_token {
+            TokenType::OpenBraces => {
+                self.object_pattern_expression()?;
+            }
+            TokenType::OpenBracket => {
+                self.delimited_list(
+                    TokenType::OpenBracket,
+                    TokenType::CloseBracket,
+                    Self::expression,
+                )?;
             }
             _ => {
+                self.eat_identifier();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parses the body of a control-flow statement, which is either a block or a single
+    /// statement.
+    fn body_statement(&mut self) -> ParseResult<Box<ASTNode>> {
+        self.skip_newlines();
+        let start = self.lexer.token_start();
+        let body = self.statement()?.unwrap_or(ASTNode::Unknown(Unknown {
+            span: Span::new(start, self.prev_end),
+        }));
+
+        Ok(Box::new(body))
+    }
+
+    fn if_statement(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+        let test = self.statement_head()?;
+        let consequent = self.body_statement()?;
+
+        let alternate = match self.at_keyword("else") {
+            true => {
                 self.advance_token();
-                None
+                Some(self.body_statement()?)
+            }
+            false => None,
+        };
+
+        Ok(ASTNode::IfStatement(IfStatement {
+            test,
+            consequent,
+            alternate,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    fn for_statement(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+        if self.curr_token == TokenType::Identifier(String::from("await")) {
+            self.advance_token();
+        }
+        let head = self.statement_head()?;
+        let body = self.body_statement()?;
+
+        Ok(ASTNode::ForStatement(ForStatement {
+            head,
+            body,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    fn while_statement(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+        let test = self.statement_head()?;
+        let body = self.body_statement()?;
+
+        Ok(ASTNode::WhileStatement(WhileStatement {
+            test,
+            body,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    fn do_while_statement(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+        let body = self.body_statement()?;
+        if !self.at_keyword("while") {
+            return Err(self.error("'while'"));
+        }
+        self.advance_token();
+        let test = self.statement_head()?;
+
+        Ok(ASTNode::DoWhileStatement(WhileStatement {
+            test,
+            body,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    fn switch_statement(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+        let discriminant = self.statement_head()?;
+        self.skip_newlines();
+        self.eat(&TokenType::OpenBraces)?;
+
+        let mut cases = vec![];
+        loop {
+            let is_case = self.at_keyword("case");
+            if !is_case && !self.at_keyword("default") {
+                break;
             }
+
+            let case_start = self.lexer.token_start();
+            self.advance_token();
+            let test = match is_case {
+                true => {
+                    let test = self.expression()?;
+                    self.advance_token_till(|t| t == &TokenType::Colon);
+                    Some(Box::new(test.unwrap_or(ASTNode::Unknown(Unknown {
+                        span: Span::new(case_start, self.prev_end),
+                    }))))
+                }
+                false => None,
+            };
+            self.eat(&TokenType::Colon)?;
+
+            let mut body = vec![];
+            loop {
+                let next_case = self.at_keyword("case") || self.at_keyword("default");
+                if next_case || matches!(self.curr_token, TokenType::CloseBraces | TokenType::EOF) {
+                    break;
+                }
+                self.recovering_statement(&mut body);
+            }
+
+            cases.push(SwitchCase {
+                test,
+                body,
+                span: Span::new(case_start, self.prev_end),
+            });
         }
+
+        self.skip_newlines();
+        self.eat(&TokenType::CloseBraces)?;
+
+        Ok(ASTNode::SwitchStatement(SwitchStatement {
+            discriminant,
+            cases,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    fn try_statement(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+        self.skip_newlines();
+        let block = Box::new(self.block_statement()?);
+
+        let mut handler = None;
+        if self.at_keyword("catch") {
+            let catch_start = self.lexer.token_start();
+            self.advance_token();
+            let param = match self.curr_token {
+                TokenType::OpenParen => self.parenthesized_list(Self::param)?.into_iter().next(),
+                _ => None,
+            };
+            self.skip_newlines();
+            let body = Box::new(self.block_statement()?);
+
+            handler = Some(CatchClause {
+                param: param.map(Box::new),
+                body,
+                span: Span::new(catch_start, self.prev_end),
+            });
+        }
+
+        let mut finalizer = None;
+        if self.at_keyword("finally") {
+            self.advance_token();
+            self.skip_newlines();
+            finalizer = Some(Box::new(self.block_statement()?));
+        }
+
+        Ok(ASTNode::TryStatement(TryStatement {
+            block,
+            handler,
+            finalizer,
+            span: Span::new(start, self.prev_end),
+        }))
     }
 
-    fn export_statement(&mut self) -> Option<ASTNode> {
+    fn export_statement(&mut self) -> ParseResult<Option<ASTNode>> {
         if !self.advance_token_against(vec![
             TokenType::Identifier(String::from("module")),
             TokenType::Dot,
             TokenType::Identifier(String::from("exports")),
             TokenType::Equals,
         ]) {
-            return None;
+            return Ok(None);
         }
 
         match &self.curr_token {
-            TokenType::OpenBraces => {
-                Some(ASTNode::ExportStatement(self.object_pattern_expression()))
+            TokenType::OpenBraces => Ok(Some(ASTNode::ExportStatement(
+                self.object_pattern_expression()?,
+            ))),
+            TokenType::Identifier(ident) => {
+                let node = ASTNode::ExportIdentifier(Identifier {
+                    name: ident.clone(),
+                    span: self.curr_span(),
+                });
+                self.advance_token();
+                Ok(Some(node))
             }
-            _ => None,
+            _ => Ok(None),
         }
     }
 
-    fn parse_backslash(&mut self) {
+    fn class_declaration(&mut self) -> ParseResult<Option<ASTNode>> {
+        let start = self.lexer.token_start();
         self.advance_token();
+        let name = match self.eat_identifier() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        let superclass = match self.curr_token == TokenType::Identifier(String::from("extends")) {
+            true => {
+                self.advance_token();
+                self.eat_identifier()
+            }
+            false => None,
+        };
+        self.skip_newlines();
+        self.eat(&TokenType::OpenBraces)?;
 
-        match &self.curr_token {
-            TokenType::Asterisk => loop {
-                self.advance_token_till(|t| t == &TokenType::Asterisk);
+        let mut methods = vec![];
+        loop {
+            while matches!(self.curr_token, TokenType::Newline | TokenType::Semicolon) {
                 self.advance_token();
-                if self.curr_token == TokenType::ForwardSlash {
-                    return;
+            }
+            if matches!(self.curr_token, TokenType::CloseBraces | TokenType::EOF) {
+                break;
+            }
+
+            match self.method_definition()? {
+                Some(method) => methods.push(method),
+                None => self.synchronize(),
+            }
+        }
+        self.eat(&TokenType::CloseBraces)?;
+
+        Ok(Some(ASTNode::ClassDeclaration(ClassDeclaration {
+            name,
+            superclass,
+            methods,
+            span: Span::new(start, self.prev_end),
+        })))
+    }
+
+    /// Parses a method in a class body or object literal. Returns `None`, leaving the cursor in
+    /// place, for members that aren't methods, e.g. fields.
+    fn method_definition(&mut self) -> ParseResult<Option<MethodDefinition>> {
+        let start = self.lexer.token_start();
+        let mut is_static = false;
+        let mut is_async = false;
+        let mut is_generator = false;
+
+        // modifiers are only keywords when another name follows, `static() {}` is a method
+        loop {
+            match &self.curr_token {
+                TokenType::Identifier(modifier)
+                    if matches!(modifier.as_str(), "static" | "async" | "get" | "set")
+                        && !matches!(
+                            self.lexer.lookahead(1),
+                            TokenType::OpenParen
+                                | TokenType::Equals
+                                | TokenType::Semicolon
+                                | TokenType::Colon
+                                | TokenType::Comma
+                                | TokenType::CloseBraces
+                        ) =>
+                {
+                    is_static |= modifier == "static";
+                    is_async |= modifier == "async";
+                    self.advance_token();
+                }
+                TokenType::Asterisk => {
+                    is_generator = true;
+                    self.advance_token();
+                }
+                _ => break,
+            }
+        }
+
+        let name = match &self.curr_token {
+            TokenType::Identifier(name) | TokenType::String(name) => name.clone(),
+            TokenType::CatchAll(hash) if hash == "#" => match self.lexer.lookahead(1) {
+                TokenType::Identifier(name) => {
+                    self.advance_token();
+                    format!("#{}", name)
                 }
+                _ => return Ok(None),
             },
-            TokenType::ForwardSlash => {
-                self.advance_token_till(|t| match t {
-                    TokenType::Newline | TokenType::EOF => true,
-                    _ => false,
-                });
+            _ => return Ok(None),
+        };
+        if self.lexer.lookahead(1) != TokenType::OpenParen {
+            return Ok(None);
+        }
+
+        self.advance_token();
+        let function = FunctionStatement {
+            is_async,
+            is_generator,
+            ..self.method_function(name.clone())?
+        };
+
+        Ok(Some(MethodDefinition {
+            name,
+            is_static,
+            function,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    /// Parses the parameters and body of a method.
+    fn method_function(&mut self, name: String) -> ParseResult<FunctionStatement> {
+        let start = self.lexer.token_start();
+        let params = self.parenthesized_list(Self::param)?;
+        self.skip_newlines();
+        let body = self.block_statement()?;
+
+        Ok(FunctionStatement {
+            name,
+            params,
+            body: Box::new(body),
+            is_async: false,
+            is_generator: false,
+            span: Span::new(start, self.prev_end),
+        })
+    }
+
+    /// Parses `new Foo(...)`.
+    fn new_expression(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+        let callee = match &self.curr_token {
+            TokenType::Identifier(name) => ASTNode::Identifier(Identifier {
+                name: name.clone(),
+                span: self.curr_span(),
+            }),
+            _ => return Err(self.error("class name")),
+        };
+        self.advance_token();
+
+        let arguments = match self.curr_token {
+            TokenType::OpenParen => self.parenthesized_list(Self::expression)?,
+            _ => vec![],
+        };
+        Ok(ASTNode::NewExpression(CallExpression {
+            base: Box::new(callee),
+            arguments,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    fn object_expression(&mut self) -> ParseResult<ObjectExpression> {
+        let start = self.lexer.token_start();
+        let properties = self.delimited_list(
+            TokenType::OpenBraces,
+            TokenType::CloseBraces,
+            Self::property,
+        )?;
+
+        Ok(ObjectExpression {
+            properties,
+            span: Span::new(start, self.prev_end),
+        })
+    }
+
+    fn property(&mut self) -> ParseResult<Option<ASTNode>> {
+        let start = self.lexer.token_start();
+        if self.curr_token == TokenType::Ellipsis {
+            return self.spread_element(Self::expression);
+        }
+        if let Some(method) = self.method_definition()? {
+            return Ok(Some(ASTNode::ObjectProperty(ObjectProperty {
+                key: PropertyKey::Name(method.name),
+                value: Box::new(ASTNode::FunctionStatement(method.function)),
+                span: method.span,
+            })));
+        }
+
+        let key_span = self.curr_span();
+        let shorthand = matches!(self.curr_token, TokenType::Identifier(_));
+        let key = match &self.curr_token {
+            TokenType::Identifier(name) | TokenType::String(name) | TokenType::Number(name) => {
+                let key = PropertyKey::Name(name.clone());
+                self.advance_token();
+                key
             }
-            _ => (),
+            TokenType::OpenBracket => {
+                self.advance_token();
+                self.skip_newlines();
+                let key = match self.expression()? {
+                    Some(key) => key,
+                    None => return Ok(None),
+                };
+                self.skip_newlines();
+                self.eat(&TokenType::CloseBracket)?;
+                PropertyKey::Computed(Box::new(key))
+            }
+            _ => return Ok(None),
+        };
+
+        // functions are named after their key, those under a computed key are left anonymous
+        let name = match &key {
+            PropertyKey::Name(name) => name.clone(),
+            PropertyKey::Computed(_) => String::new(),
         };
+        let value = match self.curr_token {
+            TokenType::Colon => {
+                self.advance_token();
+                self.skip_newlines();
+                match self.named_expression(&name)? {
+                    Some(value) => value,
+                    None => return Ok(None),
+                }
+            }
+            TokenType::OpenParen => ASTNode::FunctionStatement(self.method_function(name)?),
+            _ if shorthand => ASTNode::Identifier(Identifier {
+                name,
+                span: key_span,
+            }),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(ASTNode::ObjectProperty(ObjectProperty {
+            key,
+            value: Box::new(value),
+            span: Span::new(start, self.prev_end),
+        })))
+    }
+
+    /// Parses an expression assigned to `name`. A function is renamed to `name`, since that's
+    /// what it gets called by.
+    fn named_expression(&mut self, name: &str) -> ParseResult<Option<ASTNode>> {
+        Ok(self.expression()?.map(|node| match node {
+            ASTNode::FunctionStatement(fs) => ASTNode::FunctionStatement(FunctionStatement {
+                name: String::from(name),
+                ..fs
+            }),
+            node => node,
+        }))
     }
 
-    fn object_pattern_expression(&mut self) -> ObjectPattern {
-        let obj_pat_start = self.lexer.cursor.line_num;
+    fn object_pattern_expression(&mut self) -> ParseResult<ObjectPattern> {
+        let obj_pat_start = self.lexer.token_start();
         let mut properties = vec![];
-        self.eat(&TokenType::OpenBraces);
+        let mut rest = None;
+        self.eat(&TokenType::OpenBraces)?;
 
         loop {
-            if &self.curr_token == &TokenType::Newline {
+            if self.curr_token == TokenType::Newline {
                 self.advance_token();
             }
 
-            if &self.curr_token == &TokenType::CloseBraces {
+            if self.curr_token == TokenType::CloseBraces {
                 self.advance_token();
                 break;
             }
 
+            if self.curr_token == TokenType::Ellipsis {
+                self.advance_token();
+                rest = self.eat_identifier();
+                continue;
+            }
+
             let key = match self.eat_identifier() {
                 Some(ident) => ident,
                 None => break,
@@ -190,7 +1284,7 @@ impl Parser {
 
             let mut value = key.clone();
 
-            if &self.curr_token == &TokenType::Colon {
+            if self.curr_token == TokenType::Colon {
                 self.advance_token();
                 if let TokenType::Identifier(ident) = &self.curr_token {
                     value = ident.clone();
@@ -200,180 +1294,378 @@ impl Parser {
 
             properties.push(ast::Property { key, value });
 
-            if &self.curr_token == &TokenType::Comma {
+            if self.curr_token == TokenType::Comma {
                 self.advance_token();
             }
         }
 
-        ObjectPattern {
+        Ok(ObjectPattern {
             properties,
-            start: obj_pat_start,
-            end: self.lexer.cursor.line_num,
-        }
+            rest,
+            span: Span::new(obj_pat_start, self.prev_end),
+        })
     }
 
-    fn arrow_function_statement(&mut self, name: &str) -> ASTNode {
-        let start = self.lexer.cursor.line_num;
-        self.advance_token_till(|t| t == &TokenType::OpenBraces);
-        let body = self.block_statement();
+    /// Parses `(params) => body` or `param => body`. The function is anonymous until it's
+    /// assigned, see `named_expression`.
+    fn arrow_function(&mut self) -> ParseResult<FunctionStatement> {
+        let start = self.lexer.token_start();
+        let params = match &self.curr_token {
+            TokenType::Identifier(ident) => {
+                let param = ASTNode::Identifier(Identifier {
+                    name: ident.clone(),
+                    span: self.curr_span(),
+                });
+                self.advance_token();
+                vec![param]
+            }
+            _ => self.parenthesized_list(Self::param)?,
+        };
+        self.eat(&TokenType::Arrow)?;
+        self.skip_newlines();
+
+        let body = match self.curr_token {
+            TokenType::OpenBraces => self.block_statement()?,
+            _ => self.expression_body()?,
+        };
 
-        ASTNode::FunctionStatement(FunctionStatement {
-            name: String::from(name),
+        Ok(FunctionStatement {
+            name: String::new(),
+            params,
             body: Box::new(body),
-            start,
-            end: self.lexer.cursor.line_num,
+            is_async: false,
+            is_generator: false,
+            span: Span::new(start, self.prev_end),
         })
     }
 
-    fn variable_statement(&mut self) -> Option<ASTNode> {
-        let start = self.lexer.cursor.line_num;
+    /// Parses `async function` or an async arrow function.
+    fn async_function(&mut self) -> ParseResult<FunctionStatement> {
+        let start = self.lexer.token_start();
         self.advance_token();
-
-        let lhs = match &self.curr_token {
-            TokenType::Identifier(ident) => self.parse_identifier(ident.clone()),
-            TokenType::OpenBraces => ASTNode::ObjectPattern(self.object_pattern_expression()),
-            _ => return None,
+        let function = match &self.curr_token {
+            TokenType::Identifier(ident) if ident == "function" => self.function_expression()?,
+            _ => self.arrow_function()?,
         };
 
-        self.eat(&TokenType::Equals);
+        Ok(FunctionStatement {
+            is_async: true,
+            span: Span::new(start, function.span.end),
+            ..function
+        })
+    }
 
-        match &self.curr_token {
-            TokenType::OpenParen => match &lhs {
-                ASTNode::Identifier(ident) => Some(self.arrow_function_statement(&ident.name)),
-                _ => None,
-            },
-            TokenType::OpenBraces => {
-                //TODO: object declaration...
-                None
+    fn await_expression(&mut self) -> ParseResult<Option<ASTNode>> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+
+        Ok(self.unary_expression()?.map(|argument| {
+            ASTNode::AwaitExpression(AwaitExpression {
+                argument: Box::new(argument),
+                span: Span::new(start, self.prev_end),
+            })
+        }))
+    }
+
+    fn yield_expression(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.advance_token();
+        let delegate = self.curr_token == TokenType::Asterisk;
+        if delegate {
+            self.advance_token();
+        }
+
+        Ok(ASTNode::YieldExpression(YieldExpression {
+            argument: self.expression()?.map(Box::new),
+            delegate,
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    /// Parses the expression body of an arrow function, which runs until the end of the line or
+    /// a `,`, `;` or closing bracket that isn't nested inside it. The expressions found in it
+    /// are kept as a block.
+    fn expression_body(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        let mut expressions = vec![];
+        let mut depth = 0;
+
+        loop {
+            match self.curr_token {
+                TokenType::EOF
+                | TokenType::CloseParen
+                | TokenType::CloseBraces
+                | TokenType::TemplateMiddle(_)
+                | TokenType::TemplateTail(_) => break,
+                TokenType::Comma | TokenType::Semicolon | TokenType::Newline if depth == 0 => break,
+                TokenType::CloseBracket if depth == 0 => break,
+                TokenType::OpenBracket => {
+                    depth += 1;
+                    self.advance_token();
+                }
+                TokenType::CloseBracket => {
+                    depth -= 1;
+                    self.advance_token();
+                }
+                _ => match self.expression()? {
+                    Some(expression) => expressions.push(expression),
+                    None => self.advance_token(),
+                },
             }
-            TokenType::Identifier(ident) => {
-                let rhs = self.parse_identifier(ident.to_string());
-                Some(ASTNode::VariableExpression(VariableExpression {
-                    lhs: Box::new(lhs),
-                    rhs: Box::new(rhs),
-                    start,
-                    end: self.lexer.cursor.line_num,
-                }))
+        }
+
+        Ok(ASTNode::BlockStatement(BlockStatement {
+            body: Box::new(expressions),
+            span: Span::new(start, self.prev_end),
+        }))
+    }
+
+    /// Parses `const a = 1, b = 2` into a node per declarator. Declarators without a value, like
+    /// `let a;`, are left out.
+    fn variable_statement(&mut self) -> ParseResult<Vec<ASTNode>> {
+        let mut start = self.lexer.token_start();
+        let mut declarations = vec![];
+        self.advance_token();
+
+        loop {
+            declarations.extend(self.variable_declarator(start)?);
+            if self.curr_token != TokenType::Comma {
+                break;
             }
-            _ => None,
+            self.advance_token();
+            self.skip_newlines();
+            start = self.lexer.token_start();
         }
+
+        Ok(declarations)
     }
 
-    fn call_expression(&mut self, base: ASTNode) -> ASTNode {
-        let start = base.get_start().to_owned();
-        self.eat(&TokenType::OpenParen);
+    fn variable_declarator(&mut self, start: Position) -> ParseResult<Option<ASTNode>> {
+        let lhs = match &self.curr_token {
+            TokenType::Identifier(ident) => self.parse_identifier(ident.clone())?,
+            TokenType::OpenBraces => ASTNode::ObjectPattern(self.object_pattern_expression()?),
+            _ => return Ok(None),
+        };
 
-        // only care about the first parameter if its a string, for require().
-        let param = match &self.curr_token {
-            TokenType::String(s) => Some(s.to_owned()),
-            _ => None,
+        // only a destructuring pattern has to be given a value
+        if matches!(lhs, ASTNode::Identifier(_)) && self.curr_token != TokenType::Equals {
+            return Ok(None);
+        }
+        self.eat(&TokenType::Equals)?;
+        self.skip_newlines();
+
+        let rhs = match &lhs {
+            ASTNode::Identifier(ident) => self.named_expression(&ident.name.clone())?,
+            _ => self.expression()?,
         };
 
-        self.advance_token_till(|t| t == &TokenType::CloseParen);
-        self.eat(&TokenType::CloseParen);
+        match rhs {
+            // functions assigned to a variable are declared under its name
+            Some(ASTNode::FunctionStatement(fs)) if matches!(lhs, ASTNode::Identifier(_)) => {
+                Ok(Some(ASTNode::FunctionStatement(fs)))
+            }
+            Some(rhs) => Ok(Some(ASTNode::VariableExpression(VariableExpression {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                span: Span::new(start, self.prev_end),
+            }))),
+            None => Ok(None),
+        }
+    }
 
-        let call_expression = ASTNode::CallExpression(CallExpression {
-            base: Box::new(base),
-            param,
-            start,
-            end: self.lexer.cursor.line_num,
-        });
+    fn call_expression(&mut self, base: ASTNode) -> ParseResult<ASTNode> {
+        let start = base.get_span().start;
+        let arguments = self.parenthesized_list(Self::expression)?;
 
-        call_expression
+        Ok(ASTNode::CallExpression(CallExpression {
+            base: Box::new(base),
+            arguments,
+            span: Span::new(start, self.prev_end),
+        }))
     }
 
-    fn parse_identifier(&mut self, ident: String) -> ASTNode {
-        let start = self.lexer.cursor.line_num;
+    fn parse_identifier(&mut self, ident: String) -> ParseResult<ASTNode> {
         let ident_node = ASTNode::Identifier(Identifier {
             name: ident,
-            start,
-            end: self.lexer.cursor.line_num,
+            span: self.curr_span(),
         });
         self.advance_token();
 
         match &self.curr_token {
             TokenType::OpenParen => self.call_expression(ident_node),
-            TokenType::Dot => self.member_expression(ident_node),
-            _ => ident_node,
+            TokenType::Dot | TokenType::OptionalChain => self.member_expression(ident_node),
+            _ => Ok(ident_node),
         }
     }
 
-    fn member_expression(&mut self, base: ASTNode) -> ASTNode {
+    fn member_expression(&mut self, base: ASTNode) -> ParseResult<ASTNode> {
         let mut base = base;
         loop {
-            let (new_base, more) = self.member_prefix_expression(base);
+            self.continue_on_next_line();
+            let (new_base, more) = self.member_prefix_expression(base)?;
             base = new_base;
             if !more {
                 break;
             }
         }
 
-        base
+        Ok(base)
     }
 
-    fn member_prefix_expression(&mut self, base: ASTNode) -> (ASTNode, bool) {
-        let expression = match &self.curr_token {
-            &TokenType::Dot => {
-                self.eat(&TokenType::Dot);
+    fn member_prefix_expression(&mut self, base: ASTNode) -> ParseResult<(ASTNode, bool)> {
+        let expression = match self.curr_token {
+            TokenType::OptionalChain if self.lexer.lookahead(1) == TokenType::OpenParen => {
+                self.advance_token();
+                self.call_expression(base)?
+            }
+            TokenType::Dot | TokenType::OptionalChain => {
+                let start = base.get_span().start;
+                self.advance_token();
                 let property = match self.eat_identifier() {
                     Some(ident) => ident,
-                    None => return (base, false),
+                    None => return Ok((base, false)),
                 };
                 let me = MemberExpression {
                     base: Box::new(base),
                     property,
-                    start: 0,
-                    end: 0,
+                    span: Span::new(start, self.prev_end),
                 };
 
                 ASTNode::MemberExpression(me)
             }
-            &TokenType::OpenParen => self.call_expression(base),
-            _ => return (base, false),
+            TokenType::OpenBracket => {
+                let start = base.get_span().start;
+                self.advance_token();
+                self.skip_newlines();
+                let property = self.expression()?.ok_or_else(|| self.error("expression"))?;
+                self.skip_newlines();
+                self.eat(&TokenType::CloseBracket)?;
+
+                ASTNode::ComputedMemberExpression(ComputedMemberExpression {
+                    base: Box::new(base),
+                    property: Box::new(property),
+                    span: Span::new(start, self.prev_end),
+                })
+            }
+            TokenType::OpenParen => self.call_expression(base)?,
+            _ => return Ok((base, false)),
         };
 
-        (expression, true)
+        Ok((expression, true))
     }
 
     fn block_body(&mut self) -> Vec<ASTNode> {
         let mut statements = vec![];
-        while self.curr_token != TokenType::CloseBraces {
-            if let Some(statement) = self.statement() {
-                statements.push(statement);
-            }
+        while !matches!(self.curr_token, TokenType::CloseBraces | TokenType::EOF) {
+            self.recovering_statement(&mut statements);
         }
 
         statements
     }
 
-    fn block_statement(&mut self) -> ASTNode {
-        let start = self.lexer.cursor.line_num;
-        self.eat(&TokenType::OpenBraces);
+    fn block_statement(&mut self) -> ParseResult<ASTNode> {
+        let start = self.lexer.token_start();
+        self.eat(&TokenType::OpenBraces)?;
         let body = self.block_body();
-        self.eat(&TokenType::CloseBraces);
+        self.eat(&TokenType::CloseBraces)?;
 
-        ASTNode::BlockStatement(BlockStatement {
+        Ok(ASTNode::BlockStatement(BlockStatement {
             body: Box::new(body),
-            start,
-            end: self.lexer.cursor.line_num,
-        })
+            span: Span::new(start, self.prev_end),
+        }))
     }
 
-    fn function_expression(&mut self) -> Option<ASTNode> {
-        let start = self.lexer.cursor.line_num;
+    /// Parses `function name() {}` or `function* name() {}`, leaving the name empty for
+    /// anonymous functions.
+    fn function_expression(&mut self) -> ParseResult<FunctionStatement> {
+        let start = self.lexer.token_start();
         self.advance_token();
-        let name = match self.eat_identifier() {
-            Some(ident) => ident,
-            None => return None,
+        let is_generator = self.curr_token == TokenType::Asterisk;
+        if is_generator {
+            self.advance_token();
+        }
+        let name = self.eat_identifier().unwrap_or_default();
+        let params = match self.curr_token {
+            TokenType::OpenParen => self.parenthesized_list(Self::param)?,
+            _ => vec![],
         };
         self.advance_token_till(|t| t == &TokenType::OpenBraces);
-        let body = self.block_statement();
+        let body = self.block_statement()?;
 
-        Some(ASTNode::FunctionStatement(FunctionStatement {
+        Ok(FunctionStatement {
             name,
+            params,
             body: Box::new(body),
-            start,
-            end: self.lexer.cursor.line_num,
-        }))
+            is_async: false,
+            is_generator,
+            span: Span::new(start, self.prev_end),
+        })
+    }
+}
+
+/// Whether the tokens after an opening `(` close it and are followed by `=>`.
+fn closes_arrow_params(lexer: &mut Lexer) -> bool {
+    let mut depth = 1;
+    while depth > 0 {
+        match lexer.next_token() {
+            TokenType::OpenParen => depth += 1,
+            TokenType::CloseParen => depth -= 1,
+            TokenType::EOF => return false,
+            _ => (),
+        }
+    }
+
+    loop {
+        let token = lexer.next_token();
+        if !token.is_comment() {
+            return token == TokenType::Arrow;
+        }
     }
 }
+
+/// The operator and precedence of a binary operator token, higher binding tighter.
+fn binary_operator(token: &TokenType) -> Option<(String, u8)> {
+    let operator = match token {
+        TokenType::Asterisk => "*",
+        TokenType::ForwardSlash => "/",
+        TokenType::Operator(op) => op.as_str(),
+        TokenType::Identifier(op) if op == "instanceof" || op == "in" => op.as_str(),
+        _ => return None,
+    };
+    let precedence = match operator {
+        "??" | "||" => 1,
+        "&&" => 2,
+        "|" => 3,
+        "^" => 4,
+        "&" => 5,
+        "==" | "!=" | "===" | "!==" => 6,
+        "<" | ">" | "<=" | ">=" | "instanceof" | "in" => 7,
+        "<<" | ">>" | ">>>" => 8,
+        "+" | "-" => 9,
+        "*" | "/" | "%" => 10,
+        "**" => 11,
+        _ => return None,
+    };
+
+    Some((operator.to_owned(), precedence))
+}
+
+fn is_assignment_operator(op: &str) -> bool {
+    matches!(
+        op,
+        "+=" | "-="
+            | "*="
+            | "/="
+            | "%="
+            | "**="
+            | "<<="
+            | ">>="
+            | ">>>="
+            | "&="
+            | "|="
+            | "^="
+            | "&&="
+            | "||="
+            | "??="
+    )
+}
@@ -0,0 +1,126 @@
+use crate::{logger, utils, visitor::Match};
+use std::io::{self, IsTerminal};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Jsonl,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::Jsonl),
+            _ => Err(format!("unknown format '{}'", s)),
+        }
+    }
+}
+
+pub struct Printer {
+    format: Format,
+    heading: bool,
+    trace: bool,
+    current_file: Option<String>,
+}
+
+/// Formats the call path of a match, e.g. `foo -> bazz (data/mixed/baz.js:baz) -> match at line 2`.
+pub fn format_trace(m: &Match) -> String {
+    let mut parts = vec![];
+    let mut prev_file: Option<&String> = None;
+
+    for frame in &m.call_path {
+        let same_file = prev_file.is_none_or(|f| f == &frame.file_path);
+        if same_file && frame.name == frame.function {
+            parts.push(frame.name.clone());
+        } else {
+            parts.push(format!(
+                "{} ({}:{})",
+                frame.name,
+                utils::relative_path(&frame.file_path),
+                frame.function
+            ));
+        }
+        prev_file = Some(&frame.file_path);
+    }
+
+    match &m.branch {
+        Some(branch) => parts.push(format!("match at line {} in {}", m.line_num, branch)),
+        None => parts.push(format!("match at line {}", m.line_num)),
+    }
+    parts.join(" -> ")
+}
+
+/// Orders matches so those in the same file are together, keeping files in the order of their
+/// first match.
+pub fn group_by_file(matches: &[Match]) -> Vec<&Match> {
+    let mut files: Vec<&str> = vec![];
+    for m in matches {
+        if !files.contains(&m.file_path.as_str()) {
+            files.push(&m.file_path);
+        }
+    }
+
+    let mut grouped: Vec<&Match> = matches.iter().collect();
+    grouped.sort_by_key(|m| files.iter().position(|f| *f == m.file_path));
+    grouped
+}
+
+impl Printer {
+    pub fn new(format: Format, trace: bool) -> Self {
+        Self {
+            format,
+            heading: io::stdout().is_terminal(),
+            trace,
+            current_file: None,
+        }
+    }
+
+    pub fn print(&mut self, matches: &[Match]) {
+        match self.format {
+            // each file gets a single heading
+            Format::Text if self.heading => group_by_file(matches)
+                .into_iter()
+                .for_each(|m| self.print_text(m)),
+            Format::Text => matches.iter().for_each(|m| self.print_text(m)),
+            Format::Json => println!("{}", serde_json::to_string(matches).unwrap()),
+            Format::Jsonl => {
+                for m in matches {
+                    println!("{}", serde_json::to_string(m).unwrap());
+                }
+            }
+        }
+    }
+
+    fn print_text(&mut self, m: &Match) {
+        let path = utils::relative_path(&m.file_path);
+        let location = format!("{}:{}:", m.line_num, m.column);
+
+        if !self.heading {
+            println!("{}:{} {}", path, location, m.line.trim());
+            self.print_trace(m);
+            return;
+        }
+
+        if self.current_file.as_ref() != Some(&m.file_path) {
+            if self.current_file.is_some() {
+                println!();
+            }
+            println!("{}", logger::magenta(&path));
+            self.current_file = Some(m.file_path.clone());
+        }
+
+        println!("{} {}", logger::green(&location), m.line.trim());
+        self.print_trace(m);
+    }
+
+    fn print_trace(&self, m: &Match) {
+        if self.trace {
+            println!("    {}", format_trace(m));
+        }
+    }
+}
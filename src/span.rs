@@ -0,0 +1,84 @@
+use std::ops::Range;
+
+/// A location in the source. `line` and `column` are 0-based, `column` and `offset` count bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+/// The source range covered by a token or AST node, `end` being exclusive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Span { start, end }
+    }
+}
+
+/// The length of the line terminator at the start of `rest`, or 0 if there isn't one. `\n`,
+/// `\r\n`, `\r`, U+2028 and U+2029 all end a line.
+pub fn line_terminator_len(rest: &[u8]) -> usize {
+    match rest {
+        [b'\r', b'\n', ..] => 2,
+        [b'\n' | b'\r', ..] => 1,
+        [0xe2, 0x80, 0xa8 | 0xa9, ..] => 3,
+        _ => 0,
+    }
+}
+
+/// Where each line of a source starts and ends, for turning byte offsets into positions.
+#[derive(Debug, Clone)]
+pub struct LineTable {
+    /// The byte range of each line, excluding its terminator.
+    lines: Vec<Range<usize>>,
+}
+
+impl LineTable {
+    pub fn new(src: &str) -> Self {
+        let bytes = src.as_bytes();
+        let mut lines = vec![];
+        let (mut start, mut i) = (0, 0);
+
+        while i < bytes.len() {
+            match line_terminator_len(&bytes[i..]) {
+                0 => i += 1,
+                len => {
+                    lines.push(start..i);
+                    i += len;
+                    start = i;
+                }
+            }
+        }
+        lines.push(start..bytes.len());
+
+        LineTable { lines }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The byte range of the 0-based `line`, excluding its terminator.
+    pub fn line(&self, line: usize) -> Range<usize> {
+        self.lines[line].clone()
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.lines.partition_point(|l| l.start <= offset).max(1) - 1;
+        Position {
+            line,
+            column: offset - self.lines[line].start,
+            offset,
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::path::Path;
-use std::{fs, io, process};
+use std::{fs, io};
 
 pub struct OptionIterator<I> {
     pub iter: Option<I>,
@@ -25,11 +25,11 @@ impl<I> OptionIterator<I> {
 }
 
 pub fn path_exists(path: &str) -> bool {
-    if let Err(_) = fs::metadata(path) {
+    if fs::metadata(path).is_err() {
         return false;
     }
 
-    return true;
+    true
 }
 
 pub fn is_file(path: &str) -> bool {
@@ -59,12 +59,21 @@ pub fn join_path(base: &str, with: &str) -> Option<String> {
     None
 }
 
-pub fn read_file(filename: &str) -> String {
+pub fn read_file(filename: &str) -> Result<String, String> {
     match fs::read_to_string(filename) {
-        Ok(s) => s,
-        Err(err) => {
-            eprintln!("failed to read file {}: {}", filename, err.to_string());
-            process::exit(1);
-        }
+        Ok(s) => Ok(s),
+        Err(err) => Err(format!("failed to read file {}: {}", filename, err)),
+    }
+}
+
+pub fn relative_path(path: &str) -> String {
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return path.to_owned(),
+    };
+
+    match Path::new(path).strip_prefix(cwd) {
+        Ok(rel) => rel.display().to_string(),
+        Err(_) => path.to_owned(),
     }
 }
@@ -1,10 +1,16 @@
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
 use crate::{
-    ast::{ASTNode, BlockStatement, CallExpression, FunctionStatement, Program},
+    ast::{
+        ASTNode, BlockStatement, CallExpression, ClassDeclaration, ForStatement, FunctionStatement,
+        IfStatement, ObjectExpression, ObjectProperty, Program, PropertyKey, SwitchCase,
+        SwitchStatement, TryStatement, WhileStatement,
+    },
     file_scope::{ProgramScope, Symbol},
-    logger,
-    parser::Parser,
+    matcher::{self, Matcher, Region},
+    parser::{ParseError, Parser},
+    span::{LineTable, Span},
     utils,
 };
 
@@ -13,37 +19,174 @@ struct File {
     path: String,
     ast: ASTNode,
     lines: Vec<String>,
+    line_table: LineTable,
+    regions: Vec<Region>,
     lines_recorded: HashSet<usize>,
+    errors: Vec<ParseError>,
 }
 
 impl File {
-    fn new(path: &str) -> Self {
-        let src = utils::read_file(path);
-        let ast: ASTNode = Parser::new(&src).parse();
-
-        Self {
+    fn new(path: &str) -> Result<Self, String> {
+        let src = utils::read_file(path)?;
+        let (ast, errors) = Parser::new(&src).parse_recovering();
+        let line_table = LineTable::new(&src);
+        let lines = (0..line_table.len())
+            .map(|line| src[line_table.line(line)].to_string())
+            .collect();
+
+        Ok(Self {
             path: String::from(path),
             ast,
-            lines: src.split("\n").map(|s| s.to_string()).collect(),
+            lines,
+            line_table,
+            regions: matcher::classify(&src),
             lines_recorded: HashSet::new(),
-        }
+            errors: errors.into_iter().map(|e| e.with_file(path)).collect(),
+        })
+    }
+}
+
+/// A function entered on the way to a match.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallFrame {
+    /// The name the function was called by, e.g. an import alias.
+    pub name: String,
+    /// The name of the function declaration.
+    pub function: String,
+    #[serde(rename = "file")]
+    pub file_path: String,
+    /// Parameters bound to the searched value when following data flow.
+    pub bindings: Vec<String>,
+}
+
+/// A line matching the search pattern.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Match {
+    #[serde(rename = "file")]
+    pub file_path: String,
+    /// 1-based line number.
+    #[serde(rename = "line")]
+    pub line_num: usize,
+    /// 1-based byte column of the first occurrence of the pattern.
+    pub column: usize,
+    #[serde(rename = "text")]
+    pub line: String,
+    /// The function the match was found in, `None` at the top level of a file.
+    pub function: Option<String>,
+    /// The calls that led to the match, starting from the entry function.
+    pub call_path: Vec<CallFrame>,
+    /// The innermost control-flow branch the match is in, e.g. `else` or `case "admin"`.
+    pub branch: Option<String>,
+}
+
+/// The class `this` refers to inside a method. In a static method that's the class itself rather
+/// than an instance.
+#[derive(Debug, Clone)]
+struct ThisBinding {
+    class: String,
+    is_static: bool,
+}
+
+/// A control-flow branch of the current function, labelling the matches inside `span`.
+struct Branch {
+    label: String,
+    span: Span,
+}
+
+/// A function a call resolves to.
+struct Callee {
+    /// How the function was called, e.g. `svc.save`.
+    name: String,
+    /// The symbol the function is indexed under.
+    function: String,
+    this: Option<ThisBinding>,
+    /// Whether resolving the call entered the scope of another file, left again after the visit.
+    entered_file: bool,
+}
+
+/// The symbol a method is indexed under, `Class.method` when static and `Class#method` otherwise.
+fn method_key(class: &str, method: &str, is_static: bool) -> String {
+    match is_static {
+        true => format!("{}.{}", class, method),
+        false => format!("{}#{}", class, method),
     }
 }
 
 pub struct ASTVisitor {
-    needle: String,
+    matcher: Matcher,
+    data_flow: bool,
     line_num: usize,
     files: HashMap<String, File>,
     scope: ProgramScope,
+    call_stack: Vec<CallFrame>,
+    binding_matchers: Vec<Option<Matcher>>,
+    /// The control-flow branches of the current function around the visited node, innermost
+    /// last.
+    branches: Vec<Branch>,
+    this_binding: Option<ThisBinding>,
+    matches: Vec<Match>,
+    warnings: Vec<String>,
+    skipped_files: HashSet<String>,
 }
 
 impl ASTVisitor {
-    pub fn new(needle: &str) -> Self {
+    pub fn new(matcher: Matcher) -> Self {
         ASTVisitor {
             line_num: 0,
-            needle: String::from(needle),
+            matcher,
+            data_flow: false,
             files: HashMap::new(),
             scope: ProgramScope::new(),
+            call_stack: Vec::new(),
+            binding_matchers: Vec::new(),
+            branches: Vec::new(),
+            this_binding: None,
+            matches: Vec::new(),
+            warnings: Vec::new(),
+            skipped_files: HashSet::new(),
+        }
+    }
+
+    /// Problems with files that were skipped during the last search, e.g. parse errors in a
+    /// required file.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Follow the searched name through call arguments into the callee's parameters.
+    pub fn with_data_flow(mut self, data_flow: bool) -> Self {
+        self.data_flow = data_flow;
+        self
+    }
+
+    fn push_frame(&mut self, frame: CallFrame) {
+        let binding_matcher = match frame.bindings.is_empty() {
+            true => None,
+            false => Some(Matcher::identifiers(&frame.bindings)),
+        };
+
+        self.binding_matchers.push(binding_matcher);
+        self.call_stack.push(frame);
+    }
+
+    fn pop_frame(&mut self) {
+        self.binding_matchers.pop();
+        self.call_stack.pop();
+    }
+
+    fn find_in_line(&self, file: &File, line: usize) -> Option<usize> {
+        let text = &file.lines[line];
+        let offset = file.line_table.line(line).start;
+        let col = self.matcher.find_in(text, offset, &file.regions);
+
+        let binding_col = match self.binding_matchers.last() {
+            Some(Some(matcher)) => matcher.find_in(text, offset, &file.regions),
+            _ => None,
+        };
+
+        match (col, binding_col) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
         }
     }
 
@@ -53,14 +196,45 @@ impl ASTVisitor {
             None => return,
         };
 
-        let file = self.files.get_mut(file_path).unwrap();
+        let file = self.files.get(file_path).unwrap();
+        let mut found = vec![];
 
         for line in from..=until {
-            if !file.lines_recorded.contains(&line) && file.lines[line].contains(&self.needle) {
-                logger::info(format!("{}: {}", line + 1, file.lines[line].trim()).as_str());
-                file.lines_recorded.insert(line);
+            if file.lines_recorded.contains(&line) {
+                continue;
+            }
+
+            if let Some(col) = self.find_in_line(file, line) {
+                let offset = file.line_table.line(line).start + col;
+                let branch = self
+                    .branches
+                    .iter()
+                    .rev()
+                    .find(|b| (b.span.start.offset..b.span.end.offset).contains(&offset));
+
+                found.push(line);
+                self.matches.push(Match {
+                    file_path: file.path.clone(),
+                    line_num: line + 1,
+                    column: col + 1,
+                    line: file.lines[line].clone(),
+                    function: self.call_stack.last().map(|f| f.function.clone()),
+                    call_path: self.call_stack.clone(),
+                    branch: branch.map(|b| b.label.clone()),
+                });
             }
         }
+
+        let file_path = file.path.clone();
+        let file = self.files.get_mut(&file_path).unwrap();
+        file.lines_recorded.extend(found);
+    }
+
+    fn add_file(&mut self, file: File) {
+        for err in &file.errors {
+            self.warnings.push(err.to_string());
+        }
+        self.files.insert(file.path.clone(), file);
     }
 
     fn push_file_scope(&mut self, file_path: &str) -> bool {
@@ -75,7 +249,7 @@ impl ASTVisitor {
             _ => return false,
         };
 
-        return true;
+        true
     }
 
     fn init_visit(&mut self, node: &ASTNode) {
@@ -89,51 +263,124 @@ impl ASTVisitor {
             Err(e) => return Err(e.to_string()),
         };
 
-        let mut files = HashMap::new();
-        files.insert(path.clone(), File::new(&path));
-        self.files = files;
+        self.files = HashMap::new();
+        self.warnings.clear();
+        self.skipped_files.clear();
+        self.add_file(File::new(&path)?);
 
         self.scope.push_file(&path);
         Ok(self.files.get(&path).unwrap().ast.clone())
     }
 
-    pub fn search(&mut self, path: &str, func_start: Option<&str>) -> Result<(), String> {
+    pub fn search(&mut self, path: &str, func_start: Option<&str>) -> Result<Vec<Match>, String> {
         let file_ast = self.init_search(path)?;
+        self.call_stack.clear();
+        self.binding_matchers.clear();
+        self.branches.clear();
+        self.this_binding = None;
 
         match func_start {
             Some(func_start_name) => {
                 match &file_ast {
                     ASTNode::Program(prog) => self.index_block(&prog.lines),
-                    _ => return Err(format!("program not found")),
+                    _ => return Err("program not found".to_string()),
                 };
 
-                match file_ast.find_function(func_start_name) {
-                    Some(start) => self.init_visit(start),
+                let start = match file_ast.find_function(func_start_name) {
+                    Some(start) => start,
                     None => return Err(format!("function start {} not found", func_start_name)),
                 };
+
+                self.push_frame(CallFrame {
+                    name: func_start_name.to_owned(),
+                    function: func_start_name.to_owned(),
+                    file_path: self.scope.current().unwrap().file_path.clone(),
+                    bindings: vec![],
+                });
+                self.init_visit(start);
             }
             None => self.init_visit(&file_ast),
         };
 
-        Ok(())
+        Ok(std::mem::take(&mut self.matches))
     }
 
     fn visit_node(&mut self, node: &ASTNode) {
         let start = node.get_start();
-        self.grep(self.line_num, start);
+        match node {
+            // the head of a control-flow statement can share its line with a branch, so that line
+            // is left for the statement to grep once its branches are known
+            ASTNode::IfStatement(_)
+            | ASTNode::ForStatement(_)
+            | ASTNode::WhileStatement(_)
+            | ASTNode::DoWhileStatement(_)
+            | ASTNode::SwitchStatement(_)
+            | ASTNode::TryStatement(_) => {
+                if start > 0 {
+                    self.grep(self.line_num, start - 1);
+                }
+            }
+            _ => self.grep(self.line_num, start),
+        }
         self.line_num = start;
 
         match node {
-            ASTNode::BlockStatement(bs) => self.visit_block_statement(&bs),
+            ASTNode::BlockStatement(bs) => self.visit_block_statement(bs),
+            ASTNode::IfStatement(is) => self.visit_if_statement(is),
+            ASTNode::ForStatement(fs) => self.visit_for_statement(fs),
+            ASTNode::WhileStatement(ws) => self.visit_while_statement(ws),
+            ASTNode::DoWhileStatement(ws) => self.visit_do_while_statement(ws),
+            ASTNode::SwitchStatement(ss) => self.visit_switch_statement(ss),
+            ASTNode::TryStatement(ts) => self.visit_try_statement(ts),
             ASTNode::CallExpression(ce) => self.visit_call_expression(ce),
+            ASTNode::NewExpression(ne) => self.visit_new_expression(ne),
             ASTNode::Identifier(_) => (),
-            ASTNode::FunctionStatement(fs) => self.visit_function(&fs),
+            ASTNode::FunctionStatement(fs) => self.visit_function(fs),
             ASTNode::Program(prog) => self.visit_prog(prog),
 
+            ASTNode::TemplateLiteral(tl) => tl.expressions.iter().for_each(|e| self.visit_node(e)),
+            ASTNode::VariableExpression(ve) => self.visit_node(&ve.rhs),
+
+            ASTNode::ClassDeclaration(_) => (),
             ASTNode::ExportStatement(_) => (),
-            ASTNode::VariableExpression(_) => (),
-            ASTNode::MemberExpression(_) => (),
+            ASTNode::ExportIdentifier(_) => (),
+            ASTNode::MemberExpression(me) => self.visit_node(&me.base),
+            ASTNode::ComputedMemberExpression(cme) => {
+                self.visit_node(&cme.base);
+                self.visit_node(&cme.property);
+            }
+            ASTNode::BinaryExpression(be) | ASTNode::AssignmentExpression(be) => {
+                self.visit_node(&be.left);
+                self.visit_node(&be.right);
+            }
+            ASTNode::UnaryExpression(ue) => self.visit_node(&ue.argument),
+            ASTNode::ConditionalExpression(ce) => {
+                self.visit_node(&ce.test);
+                self.visit_node(&ce.consequent);
+                self.visit_node(&ce.alternate);
+            }
+            ASTNode::ArrayExpression(ae) | ASTNode::SequenceExpression(ae) => {
+                ae.elements.iter().for_each(|e| self.visit_node(e))
+            }
             ASTNode::ObjectPattern(_) => (),
+            ASTNode::ObjectExpression(oe) => self.visit_object_expression(oe),
+            ASTNode::ObjectProperty(op) => {
+                if let PropertyKey::Computed(key) = &op.key {
+                    self.visit_node(key);
+                }
+                self.visit_node(&op.value)
+            }
+            ASTNode::StringLiteral(_) => (),
+            ASTNode::NumericLiteral(_) => (),
+            ASTNode::RegexLiteral(_) => (),
+            ASTNode::SpreadElement(se) => self.visit_node(&se.argument),
+            ASTNode::AwaitExpression(ae) => self.visit_node(&ae.argument),
+            ASTNode::YieldExpression(ye) => {
+                if let Some(argument) = &ye.argument {
+                    self.visit_node(argument)
+                }
+            }
+            ASTNode::Unknown(_) => (),
         };
 
         // previous block statement has already exhausted everything and left the scope.
@@ -151,14 +398,22 @@ impl ASTVisitor {
     }
 
     fn index_export(&mut self, required_file: &str, lhs: &ASTNode) {
-        let file = match self.files.get(required_file) {
-            Some(file) => &file,
-            None => {
-                self.files
-                    .insert(String::from(required_file), File::new(required_file));
-                self.files.get(required_file).unwrap()
-            }
-        };
+        if self.skipped_files.contains(required_file) {
+            return;
+        }
+
+        if !self.files.contains_key(required_file) {
+            match File::new(required_file) {
+                Ok(file) => self.add_file(file),
+                Err(e) => {
+                    self.warnings.push(format!("skipping {}", e));
+                    self.skipped_files.insert(String::from(required_file));
+                    return;
+                }
+            };
+        }
+
+        let file = self.files.get(required_file).unwrap();
 
         match lhs {
             ASTNode::ObjectPattern(op) => {
@@ -183,9 +438,24 @@ impl ASTVisitor {
                             file_path: file.path.clone(),
                         },
                     )
+                } else if let Some(export) = file.ast.find_export_identifier() {
+                    let node = match file.ast.find_class(&export.name) {
+                        Some(cd) => ASTNode::ClassDeclaration(cd.clone()),
+                        None => match file.ast.find_function(&export.name) {
+                            Some(func) => func.clone(),
+                            None => return,
+                        },
+                    };
+                    self.scope.insert_symbol(
+                        &ident.name,
+                        Symbol {
+                            node,
+                            file_path: file.path.clone(),
+                        },
+                    )
                 }
             }
-            _ => return,
+            _ => (),
         }
     }
 
@@ -198,35 +468,128 @@ impl ASTVisitor {
                 }
             }
 
-            match node {
-                ASTNode::FunctionStatement(fs) => self.scope.insert_symbol(
+            if let ASTNode::FunctionStatement(fs) = node {
+                self.scope.insert_symbol(
                     &fs.name,
                     Symbol {
                         node: ASTNode::FunctionStatement(fs.clone()),
                         file_path: current_file.clone(),
                     },
-                ),
-                _ => (),
+                )
+            }
+
+            if let ASTNode::ClassDeclaration(cd) = node {
+                self.index_class(cd, &current_file);
+            }
+
+            if let ASTNode::VariableExpression(ve) = node {
+                match (ve.lhs.as_ref(), ve.rhs.as_ref()) {
+                    (ASTNode::Identifier(ident), ASTNode::ObjectExpression(oe)) => {
+                        self.index_object(&ident.name, oe, &current_file)
+                    }
+                    // an instance, whose methods are looked up on its class when called
+                    (ASTNode::Identifier(ident), ASTNode::NewExpression(_)) => {
+                        self.scope.insert_symbol(
+                            &ident.name,
+                            Symbol {
+                                node: ve.rhs.as_ref().clone(),
+                                file_path: current_file.clone(),
+                            },
+                        )
+                    }
+                    _ => (),
+                }
             }
         }
     }
 
+    /// Indexes an object assigned to `name` along with the functions among its properties, which
+    /// go under `name.key`.
+    fn index_object(&mut self, name: &str, object: &ObjectExpression, file_path: &str) {
+        self.scope.insert_symbol(
+            name,
+            Symbol {
+                node: ASTNode::ObjectExpression(object.clone()),
+                file_path: file_path.to_owned(),
+            },
+        );
+
+        for prop in &object.properties {
+            if let ASTNode::ObjectProperty(ObjectProperty {
+                key: PropertyKey::Name(key),
+                value,
+                ..
+            }) = prop
+            {
+                if let ASTNode::FunctionStatement(_) = value.as_ref() {
+                    self.scope.insert_symbol(
+                        &format!("{}.{}", name, key),
+                        Symbol {
+                            node: value.as_ref().clone(),
+                            file_path: file_path.to_owned(),
+                        },
+                    )
+                }
+            }
+        }
+    }
+
+    /// Indexes a class along with its static and instance methods, see `method_key`.
+    fn index_class(&mut self, class: &ClassDeclaration, file_path: &str) {
+        self.scope.insert_symbol(
+            &class.name,
+            Symbol {
+                node: ASTNode::ClassDeclaration(class.clone()),
+                file_path: file_path.to_owned(),
+            },
+        );
+
+        for method in &class.methods {
+            self.scope.insert_symbol(
+                &method_key(&class.name, &method.name, method.is_static),
+                Symbol {
+                    node: ASTNode::FunctionStatement(method.function.clone()),
+                    file_path: file_path.to_owned(),
+                },
+            )
+        }
+    }
+
     fn visit_block(&mut self, lines: &Vec<ASTNode>) {
         self.index_block(lines);
 
         for node in lines {
             match node {
-                ASTNode::FunctionStatement(fs) => {
-                    if fs.start > 0 {
-                        self.grep(self.line_num, fs.start - 1);
-                    }
-                    self.line_num = fs.end + 1;
+                ASTNode::FunctionStatement(_) | ASTNode::ClassDeclaration(_) => {
+                    self.skip_declaration(node)
                 }
                 _ => self.visit_node(node),
             };
         }
     }
 
+    /// Greps up to a declaration and moves past it. Its body is only visited when called.
+    fn skip_declaration(&mut self, node: &ASTNode) {
+        let span = node.get_span();
+        if span.start.line > 0 {
+            self.grep(self.line_num, span.start.line - 1);
+        }
+        self.line_num = span.end.line + 1;
+    }
+
+    fn visit_object_expression(&mut self, object: &ObjectExpression) {
+        for prop in &object.properties {
+            match prop {
+                ASTNode::ObjectProperty(op)
+                    if matches!(op.value.as_ref(), ASTNode::FunctionStatement(_)) =>
+                {
+                    self.skip_declaration(prop)
+                }
+                _ => self.visit_node(prop),
+            }
+        }
+    }
+
     fn visit_prog(&mut self, program: &Program) {
         self.visit_block(program.lines.as_ref());
     }
@@ -240,41 +603,341 @@ impl ASTVisitor {
         self.visit_block(block_statement.body.as_ref());
     }
 
+    /// Labels the matches inside `span`. A statement registers all of its branches before
+    /// visiting any of it, since its head can share a line with a body.
+    fn push_branch(&mut self, label: &str, span: Span) {
+        self.branches.push(Branch {
+            label: String::from(label),
+            span,
+        });
+    }
+
+    fn visit_if_statement(&mut self, if_statement: &IfStatement) {
+        let depth = self.branches.len();
+        self.push_branch("if", if_statement.consequent.get_span());
+        if let Some(alternate) = &if_statement.alternate {
+            self.push_branch("else", alternate.get_span());
+        }
+
+        if_statement.test.iter().for_each(|e| self.visit_node(e));
+        self.visit_node(&if_statement.consequent);
+        if let Some(alternate) = &if_statement.alternate {
+            self.visit_node(alternate);
+        }
+        self.branches.truncate(depth);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ForStatement) {
+        self.push_branch("for", for_statement.body.get_span());
+        for_statement.head.iter().for_each(|e| self.visit_node(e));
+        self.visit_node(&for_statement.body);
+        self.branches.pop();
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &WhileStatement) {
+        self.push_branch("while", while_statement.body.get_span());
+        while_statement.test.iter().for_each(|e| self.visit_node(e));
+        self.visit_node(&while_statement.body);
+        self.branches.pop();
+    }
+
+    fn visit_do_while_statement(&mut self, while_statement: &WhileStatement) {
+        self.push_branch("do", while_statement.body.get_span());
+        self.visit_node(&while_statement.body);
+        while_statement.test.iter().for_each(|e| self.visit_node(e));
+        self.branches.pop();
+    }
+
+    fn visit_switch_statement(&mut self, switch_statement: &SwitchStatement) {
+        let depth = self.branches.len();
+        for case in &switch_statement.cases {
+            self.push_branch(&case_label(case), case.span);
+        }
+
+        switch_statement
+            .discriminant
+            .iter()
+            .for_each(|e| self.visit_node(e));
+
+        self.scope.push_block();
+        for case in &switch_statement.cases {
+            self.grep(self.line_num, case.span.start.line);
+            self.line_num = case.span.start.line;
+
+            if let Some(test) = &case.test {
+                self.visit_node(test);
+            }
+            self.visit_block(&case.body);
+
+            self.grep(self.line_num, case.span.end.line);
+            self.line_num = case.span.end.line;
+        }
+        self.scope.pop();
+        self.branches.truncate(depth);
+    }
+
+    fn visit_try_statement(&mut self, try_statement: &TryStatement) {
+        let depth = self.branches.len();
+        self.push_branch("try", try_statement.block.get_span());
+        if let Some(handler) = &try_statement.handler {
+            self.push_branch("catch", handler.span);
+        }
+        if let Some(finalizer) = &try_statement.finalizer {
+            self.push_branch("finally", finalizer.get_span());
+        }
+
+        self.visit_node(&try_statement.block);
+        if let Some(handler) = &try_statement.handler {
+            self.visit_node(&handler.body);
+        }
+        if let Some(finalizer) = &try_statement.finalizer {
+            self.visit_node(finalizer);
+        }
+        self.branches.truncate(depth);
+    }
+
     fn visit_call_expression(&mut self, call_expr: &CallExpression) {
-        let (base_name, call_name) = match call_expr.base.as_ref() {
-            ASTNode::Identifier(ident) => (&ident.name, &ident.name),
-            ASTNode::MemberExpression(me) => (&me.get_base().name, &me.property),
+        match call_expr.base.as_ref() {
+            ASTNode::Identifier(_) => (),
+            ASTNode::MemberExpression(me) => self.visit_node(&me.base),
+            // e.g. `(function () {})()` or `get()()`
+            base => self.visit_node(base),
+        }
+        for arg in &call_expr.arguments {
+            self.visit_node(arg);
+        }
+
+        if let Some(callee) = self.resolve_callee(&call_expr.base) {
+            self.visit_callee(callee, call_expr);
+        }
+    }
+
+    /// Follows `new Foo()` into the class constructor.
+    fn visit_new_expression(&mut self, new_expr: &CallExpression) {
+        for arg in &new_expr.arguments {
+            self.visit_node(arg);
+        }
+
+        let class = match new_expr.base.as_ref() {
+            ASTNode::Identifier(ident) => ident,
             _ => return,
         };
-
-        let base_symbol = match self.scope.find_symbol(&base_name).map(|s| s.clone()) {
-            Some(symbol) => symbol,
+        let (symbol, function, this) = match self.class_method(&class.name, "constructor", false) {
+            Some(method) => method,
             None => return,
         };
 
-        if base_symbol.file_path != self.scope.current().unwrap().file_path {
-            if !self.push_file_scope(&base_symbol.file_path) {
-                return;
-            }
+        if let Some(entered_file) = self.enter_file(&symbol.file_path, &function) {
+            let name = format!("new {}", class.name);
+            self.visit_callee(
+                Callee {
+                    name,
+                    function,
+                    this,
+                    entered_file,
+                },
+                new_expr,
+            );
         }
+    }
+
+    /// Finds the class `class_name` refers to and the symbol its method is indexed under.
+    fn class_method(
+        &self,
+        class_name: &str,
+        method: &str,
+        is_static: bool,
+    ) -> Option<(Symbol, String, Option<ThisBinding>)> {
+        let symbol = self.scope.find_symbol(class_name).cloned()?;
+        let class = match &symbol.node {
+            ASTNode::ClassDeclaration(cd) => cd.name.clone(),
+            _ => return None,
+        };
+        let function = method_key(&class, method, is_static);
+
+        Some((symbol, function, Some(ThisBinding { class, is_static })))
+    }
 
-        let func_name = match &base_symbol.node {
-            // TODO: handle nested member expressions e.g. foo.bar.baz()
-            ASTNode::ExportStatement(es) => match es.get_value(&call_name) {
-                Some(v) => v,
-                None => return,
+    /// Resolves the function a call goes to, entering the scope of the file it's declared in.
+    fn resolve_callee(&mut self, base: &ASTNode) -> Option<Callee> {
+        let (symbol, name, function, this) = match base {
+            ASTNode::Identifier(ident) => {
+                let symbol = self.scope.find_symbol(&ident.name).cloned()?;
+                let function = match &symbol.node {
+                    ASTNode::FunctionStatement(fs) => fs.name.clone(),
+                    _ => return None,
+                };
+                (symbol, ident.name.clone(), function, None)
+            }
+            ASTNode::MemberExpression(me) => match me.base.as_ref() {
+                ASTNode::Identifier(ident) if ident.name == "this" => {
+                    let this = self.this_binding.clone()?;
+                    let function = method_key(&this.class, &me.property, this.is_static);
+                    self.scope.find_symbol(&function)?;
+
+                    let name = format!("this.{}", me.property);
+                    return Some(Callee {
+                        name,
+                        function,
+                        this: Some(this),
+                        entered_file: false,
+                    });
+                }
+                ASTNode::NewExpression(ne) => {
+                    let class = match ne.base.as_ref() {
+                        ASTNode::Identifier(ident) => &ident.name,
+                        _ => return None,
+                    };
+                    let (symbol, function, this) = self.class_method(class, &me.property, false)?;
+                    let name = format!("new {}().{}", class, me.property);
+                    (symbol, name, function, this)
+                }
+                _ => {
+                    let base_name = &me.get_base()?.name;
+                    let name = format!("{}.{}", base_name, me.property);
+                    let symbol = self.scope.find_symbol(base_name).cloned()?;
+
+                    match &symbol.node {
+                        // TODO: handle nested member expressions e.g. foo.bar.baz()
+                        ASTNode::ExportStatement(es) => {
+                            let function = es.get_value(&me.property)?.clone();
+                            (symbol, name, function, None)
+                        }
+                        ASTNode::ClassDeclaration(_) => {
+                            let (symbol, function, this) =
+                                self.class_method(base_name, &me.property, true)?;
+                            (symbol, name, function, this)
+                        }
+                        ASTNode::NewExpression(ne)
+                            if matches!(me.base.as_ref(), ASTNode::Identifier(_)) =>
+                        {
+                            let class = match ne.base.as_ref() {
+                                ASTNode::Identifier(ident) => &ident.name,
+                                _ => return None,
+                            };
+                            let (symbol, function, this) =
+                                self.class_method(class, &me.property, false)?;
+                            (symbol, name, function, this)
+                        }
+                        ASTNode::ObjectExpression(oe)
+                            if matches!(me.base.as_ref(), ASTNode::Identifier(_)) =>
+                        {
+                            let function = match oe.get_property(&me.property)?.value.as_ref() {
+                                ASTNode::FunctionStatement(_) => name.clone(),
+                                ASTNode::Identifier(ident) => ident.name.clone(),
+                                _ => return None,
+                            };
+                            (symbol, name, function, None)
+                        }
+                        _ => return None,
+                    }
+                }
             },
-            ASTNode::FunctionStatement(fs) => &fs.name,
-            _ => return,
+            _ => return None,
         };
 
-        let func_symbol = match self.scope.find_symbol(&func_name).map(|s| s.clone()) {
-            Some(symbol) => symbol,
-            None => return,
+        let entered_file = self.enter_file(&symbol.file_path, &function)?;
+        Some(Callee {
+            name,
+            function,
+            this,
+            entered_file,
+        })
+    }
+
+    /// Enters the scope of `file_path` if it isn't the current file. Returns whether it did, or
+    /// `None` when `function` can't be found there, leaving the scope again.
+    fn enter_file(&mut self, file_path: &str, function: &str) -> Option<bool> {
+        let entered = file_path != self.scope.current().unwrap().file_path;
+        if entered && !self.push_file_scope(file_path) {
+            self.scope.pop_file();
+            return None;
+        }
+
+        if self.scope.find_symbol(function).is_none() {
+            if entered {
+                self.scope.pop_file();
+            }
+            return None;
+        }
+
+        Some(entered)
+    }
+
+    fn visit_callee(&mut self, callee: Callee, call_expr: &CallExpression) {
+        let func_symbol = self.scope.find_symbol(&callee.function).cloned().unwrap();
+
+        // a recursive call, the function is already being visited
+        if self
+            .call_stack
+            .iter()
+            .any(|f| f.function == callee.function && f.file_path == func_symbol.file_path)
+        {
+            if callee.entered_file {
+                self.scope.pop_file();
+            }
+            return;
+        }
+
+        let bindings = match &func_symbol.node {
+            ASTNode::FunctionStatement(fs) if self.data_flow => {
+                self.bind_arguments(&call_expr.arguments, &fs.params)
+            }
+            _ => vec![],
         };
 
+        self.push_frame(CallFrame {
+            name: callee.name,
+            function: callee.function,
+            file_path: func_symbol.file_path.clone(),
+            bindings,
+        });
+        let branches = std::mem::take(&mut self.branches);
+        let this_binding = std::mem::replace(&mut self.this_binding, callee.this);
         self.line_num = func_symbol.node.get_start();
         self.visit_node(&func_symbol.node);
-        self.line_num = call_expr.start;
+        self.this_binding = this_binding;
+        self.branches = branches;
+        self.pop_frame();
+        if callee.entered_file {
+            self.scope.pop_file();
+        }
+        self.line_num = call_expr.span.start.line;
+    }
+
+    /// Returns the parameters that receive an argument holding the searched value.
+    fn bind_arguments(&self, arguments: &[ASTNode], params: &[ASTNode]) -> Vec<String> {
+        let bindings = self.call_stack.last().map(|f| &f.bindings);
+        let mut bound = vec![];
+
+        for (arg, param) in arguments.iter().zip(params) {
+            // arguments after a spread no longer line up with the parameters
+            if let ASTNode::SpreadElement(_) = arg {
+                break;
+            }
+
+            let (ASTNode::Identifier(arg), ASTNode::Identifier(param)) = (arg, param) else {
+                continue;
+            };
+
+            if self.matcher.is_match_whole(&arg.name)
+                || bindings.is_some_and(|b| b.contains(&arg.name))
+            {
+                bound.push(param.name.clone());
+            }
+        }
+
+        bound
+    }
+}
+
+/// Labels a switch case by its test when that's a simple value, e.g. `case "admin"`.
+fn case_label(case: &SwitchCase) -> String {
+    match case.test.as_deref() {
+        None => String::from("default"),
+        Some(ASTNode::StringLiteral(sl)) => format!("case {:?}", sl.value),
+        Some(ASTNode::NumericLiteral(nl)) => format!("case {}", nl.raw),
+        Some(ASTNode::Identifier(ident)) => format!("case {}", ident.name),
+        Some(_) => String::from("case"),
     }
 }
@@ -1,8 +1,39 @@
+use cjsgrep::{
+    ast::{ASTNode, Position, Span},
+    lexer::{Lexer, TokenType},
+    matcher::{Matcher, MatcherOptions},
+    parser::Parser,
+    printer::group_by_file,
+    utils,
+    visitor::ASTVisitor,
+};
 use std::process;
 
+fn search_output(filename: &str, pattern: &str, args: &[&str]) -> String {
+    let bytes = process::Command::new("target/debug/cjsgrep")
+        .arg(pattern)
+        .arg(filename)
+        .args(args)
+        .output()
+        .unwrap()
+        .stdout;
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
 fn test_search(filename: &str, pattern: &str, func_start: Option<&str>, expected_out: Vec<&str>) {
+    test_search_with_args(filename, pattern, func_start, &[], expected_out)
+}
+
+fn test_search_with_args(
+    filename: &str,
+    pattern: &str,
+    func_start: Option<&str>,
+    args: &[&str],
+    expected_out: Vec<&str>,
+) {
     let mut cmd = process::Command::new("target/debug/cjsgrep");
-    cmd.arg(pattern).arg(filename);
+    cmd.arg(pattern).arg(filename).args(args);
 
     if let Some(func_start) = func_start {
         cmd.arg(format!("-n={}", func_start));
@@ -17,7 +48,7 @@ fn test_search(filename: &str, pattern: &str, func_start: Option<&str>, expected
 
     assert_eq!(lines.len(), expected_out.len());
     for (line, expected) in lines.iter().zip(expected_out.iter()) {
-        assert_eq!(line.contains(expected), true);
+        assert!(line.contains(expected));
     }
 }
 
@@ -49,6 +80,31 @@ mod tests {
         test_search("data/comments.js", "pin", Some("foo"), vec!["pin = bar;"]);
     }
 
+    #[test]
+    fn comment_trivia() {
+        // the apostrophe in the comment doesn't start a string
+        test_search(
+            "data/comment-trivia.js",
+            "pin",
+            Some("foo"),
+            vec!["3:3: pin = 'pin'; // pin", "pin = bar;"],
+        );
+        test_search_with_args(
+            "data/comment-trivia.js",
+            "pin",
+            Some("foo"),
+            &["--match-kind=comment"],
+            vec!["3:19: pin = 'pin'; // pin"],
+        );
+        test_search_with_args(
+            "data/comment-trivia.js",
+            "// pin",
+            Some("foo"),
+            &["--match-kind=code"],
+            vec![],
+        );
+    }
+
     #[test]
     fn multi_file() {
         test_search(
@@ -74,4 +130,881 @@ mod tests {
             vec!["obj = 1;", "obj = 2;", "obj = 3;"],
         );
     }
+
+    #[test]
+    fn file_paths() {
+        test_search(
+            "data/import-test.js",
+            "baz",
+            Some("foo"),
+            vec![
+                "data/import-test.js:11:3: bazz();",
+                "data/mixed/baz.js:1:10: function baz(obj) {",
+                "data/mixed/baz.js:2:7: obj.baz = 1;",
+                "data/mixed/baz.js:6:10: function baz2(obj) {",
+                "data/mixed/baz.js:7:7: obj.baz = 2;",
+            ],
+        );
+    }
+
+    #[test]
+    fn trace() {
+        test_search_with_args(
+            "data/import-test.js",
+            "obj.baz",
+            Some("foo"),
+            &["--trace"],
+            vec![
+                "data/mixed/baz.js:2:3: obj.baz = 1;",
+                "foo -> bazz (data/mixed/baz.js:baz) -> match at line 2",
+                "data/mixed/baz.js:7:3: obj.baz = 2;",
+                "foo -> double (data/mixed/baz.js:baz2) -> match at line 7",
+            ],
+        );
+    }
+
+    #[test]
+    fn regex_pattern() {
+        test_search(
+            "data/import-test.js",
+            r"obj\.[a-z]+ = \d",
+            Some("foo"),
+            vec!["obj.baz = 1;", "obj.baz = 2;"],
+        );
+    }
+
+    #[test]
+    fn fixed_strings() {
+        test_search_with_args("data/import-test.js", "obj.+", Some("foo"), &["-F"], vec![]);
+        test_search_with_args(
+            "data/fixed-strings.js",
+            "obj.baz",
+            Some("foo"),
+            &["-F"],
+            vec!["2:3: obj.baz = 1;"],
+        );
+    }
+
+    #[test]
+    fn ignore_case() {
+        test_search_with_args(
+            "data/import-test.js",
+            "OBJ.BAZ",
+            Some("foo"),
+            &["-i"],
+            vec!["obj.baz = 1;", "obj.baz = 2;"],
+        );
+    }
+
+    #[test]
+    fn whole_word() {
+        test_search_with_args(
+            "data/import-test.js",
+            "baz",
+            Some("foo"),
+            &["-w"],
+            vec!["function baz(obj) {", "obj.baz = 1;", "obj.baz = 2;"],
+        );
+    }
+
+    #[test]
+    fn match_kind() {
+        test_search_with_args(
+            "data/match-kind.js",
+            "user",
+            Some("foo"),
+            &["--match-kind=identifier"],
+            vec!["const user = \"user\";", "log(user);"],
+        );
+        test_search_with_args(
+            "data/match-kind.js",
+            "user",
+            Some("foo"),
+            &["--match-kind=string"],
+            vec!["3:17: const user = \"user\";", "return 'user';"],
+        );
+        test_search_with_args(
+            "data/match-kind.js",
+            "user",
+            Some("foo"),
+            &["--match-kind=comment"],
+            vec!["// the user object", "/* user", "user */"],
+        );
+        // the comment lines between the code are skipped
+        test_search_with_args(
+            "data/match-kind.js",
+            "user",
+            Some("foo"),
+            &["--match-kind=code"],
+            vec![
+                "3:9: const user = \"user\";",
+                "4:7: log(user);",
+                "7:11: return 'user';",
+            ],
+        );
+    }
+
+    #[test]
+    fn data_flow() {
+        test_search(
+            "data/data-flow.js",
+            "obj",
+            Some("foo"),
+            vec!["const obj", "baz(obj);"],
+        );
+        test_search_with_args(
+            "data/data-flow.js",
+            "obj",
+            Some("foo"),
+            &["--data-flow"],
+            vec![
+                "const obj",
+                "baz(obj);",
+                "function baz(o) {",
+                "o.visited = true;",
+                "return helper(1, o);",
+                "function helper(n, item) {",
+                "log(item);",
+            ],
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        let err = Parser::new("function foo() {\n  bar(a b);\n")
+            .parse()
+            .unwrap_err();
+
+        assert_eq!(err.line, 3);
+        assert_eq!(err.column, 1);
+        assert_eq!(err.expected, "'}'");
+        assert_eq!(err.found, "end of file");
+    }
+
+    #[test]
+    fn string_escapes() {
+        test_search(
+            "data/strings.js",
+            "user",
+            Some("foo"),
+            vec![
+                "1:14: function foo(user) {",
+                "2:23: log('don\\'t touch', user);",
+                "3:24: log(\"say \\\"hi\\\" to\", user);",
+                "4:7: bar(user);",
+                "7:14: function bar(user) {",
+                "8:3: user.seen = true;",
+            ],
+        );
+
+        let ast = Parser::new(r#"const m = require("./a\x2fb\u{2F}c\n");"#)
+            .parse()
+            .unwrap();
+        let require = match ast {
+            ASTNode::Program(prog) => prog.lines[0].try_get_require_file().unwrap().0,
+            _ => panic!("expected a program"),
+        };
+        assert_eq!(require, "./a/b/c\n");
+    }
+
+    #[test]
+    fn unicode_identifiers() {
+        test_search(
+            "data/unicode.js",
+            r"\$scope",
+            Some("foo"),
+            vec![
+                "13:9: café($scope, el);",
+                "2:16: function café($scope, _$el) {",
+                "4:3: $scope.émoji = `✓ ${_$el}`;",
+                "5:10: naïve($scope);",
+                "8:17: function naïve($scope) {",
+                "9:3: $scope.done = true;",
+            ],
+        );
+        test_search_with_args(
+            "data/unicode.js",
+            "ü",
+            Some("foo"),
+            &["--match-kind=identifier"],
+            vec!["3:11: const grüße"],
+        );
+    }
+
+    #[test]
+    fn operators() {
+        test_search_with_args(
+            "data/operators.js",
+            "user",
+            Some("foo"),
+            &["--data-flow"],
+            vec![
+                "1:14: function foo(user, ...rest) {",
+                "2:17: const name = (user);",
+                "7:3: user?.profile?.reload(user);",
+                "8:10: load?.(user);",
+                "13:15: function load(user) {",
+                "14:7: log(user);",
+                "9:19: notify(...rest, user);",
+                "10:3: user.visits += 1;",
+            ],
+        );
+
+        let (ast, errors) =
+            Parser::new("const a = (b);\nconst c = (d) => {};\n").parse_recovering();
+        assert!(errors.is_empty());
+        let lines = match ast {
+            ASTNode::Program(prog) => prog.lines,
+            _ => panic!("expected a program"),
+        };
+        match &lines[0] {
+            ASTNode::VariableExpression(ve) => {
+                assert!(matches!(ve.rhs.as_ref(), ASTNode::Identifier(i) if i.name == "b"))
+            }
+            _ => panic!("expected a variable"),
+        }
+        assert!(matches!(&lines[1], ASTNode::FunctionStatement(fs) if fs.name == "c"));
+    }
+
+    #[test]
+    fn numbers() {
+        let mut lexer = Lexer::new("1_000n 0xFF 0o17 0b1010 3.14e-2 .5 a1 4 / 2 / 1");
+        let mut tokens = vec![];
+        loop {
+            match lexer.next_token() {
+                TokenType::EOF => break,
+                token => tokens.push(token),
+            }
+        }
+
+        let number = |s: &str| TokenType::Number(s.to_owned());
+        assert_eq!(
+            tokens,
+            vec![
+                number("1_000n"),
+                number("0xFF"),
+                number("0o17"),
+                number("0b1010"),
+                number("3.14e-2"),
+                number(".5"),
+                TokenType::Identifier("a1".to_owned()),
+                number("4"),
+                TokenType::ForwardSlash,
+                number("2"),
+                TokenType::ForwardSlash,
+                number("1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn shebang_and_bom() {
+        test_search(
+            "data/shebang.js",
+            "args",
+            Some("main"),
+            vec![
+                "3:15: function main(args) {",
+                "4:7: run(args);",
+                "7:14: function run(args) {",
+                "8:15: console.log(args);",
+            ],
+        );
+
+        let src = "\u{feff}#!/usr/bin/env node\nfunction main() {}\n";
+        let mut lexer = Lexer::new(src);
+        loop {
+            match lexer.next_token() {
+                TokenType::EOF => break,
+                token => assert!(!matches!(token, TokenType::CatchAll(_)), "{:?}", token),
+            }
+        }
+
+        let ast = Parser::new(src).parse().unwrap();
+        match ast.find_function("main") {
+            Some(func) => assert_eq!(func.get_start(), 1),
+            None => panic!("function not found"),
+        }
+    }
+
+    #[test]
+    fn line_terminators() {
+        test_search(
+            "data/crlf.js",
+            "user",
+            Some("foo"),
+            vec![
+                "1:14: function foo(user) {",
+                "2:7: bar(user);",
+                "5:14: function bar(user) {",
+                "6:3: user.seen = true;",
+            ],
+        );
+        assert!(!search_output("data/crlf.js", "user", &["-n=foo"]).contains('\r'));
+
+        let src = std::fs::read_to_string("data/crlf.js").unwrap();
+        let ast = Parser::new(&src).parse().unwrap();
+        match ast.find_function("baz") {
+            Some(func) => assert_eq!(func.get_span().start.line, 8),
+            None => panic!("function not found"),
+        }
+    }
+
+    #[test]
+    fn control_flow() {
+        let matcher = Matcher::new("user", &MatcherOptions::default()).unwrap();
+        let matches = ASTVisitor::new(matcher)
+            .search("data/control-flow.js", Some("foo"))
+            .unwrap();
+        let branches: Vec<_> = matches
+            .iter()
+            .map(|m| (m.line_num, m.branch.as_deref()))
+            .collect();
+
+        assert_eq!(
+            branches,
+            vec![
+                (1, None),
+                (2, None),
+                (38, None),
+                (39, None),
+                (3, Some("if")),
+                (4, Some("else")),
+                (5, Some("if")),
+                (7, Some("else")),
+                (10, None),
+                (11, Some("for")),
+                (14, None),
+                (17, Some("do")),
+                (18, None),
+                (20, None),
+                (22, Some("case \"admin\"")),
+                (26, Some("default")),
+                (30, Some("try")),
+                (32, Some("catch")),
+                (34, Some("finally")),
+            ]
+        );
+
+        test_search_with_args(
+            "data/control-flow.js",
+            "report",
+            Some("foo"),
+            &["--trace"],
+            vec![
+                "32:5: report(err, user);",
+                "foo -> match at line 32 in catch",
+            ],
+        );
+
+        // the label goes with the reported column when a line holds several branches
+        let matcher = Matcher::new("target", &MatcherOptions::default()).unwrap();
+        let matches = ASTVisitor::new(matcher)
+            .search("data/control-flow.js", Some("oneLine"))
+            .unwrap();
+        let columns: Vec<_> = matches
+            .iter()
+            .map(|m| (m.line_num, m.column, m.branch.as_deref()))
+            .collect();
+
+        assert_eq!(columns, vec![(43, 12, Some("if")), (44, 9, Some("try"))]);
+    }
+
+    #[test]
+    fn top_level_blocks() {
+        // closing a top-level block leaves the file's scope in place
+        test_search(
+            "data/top-level.js",
+            "pin",
+            None,
+            vec!["return value.pin;", "pin();"],
+        );
+    }
+
+    #[test]
+    fn loop_heads() {
+        let src = "for (const [k, v] of Object.entries(o)) {}\n\
+                   for (let { a, b: c } of list) {}\n\
+                   for (var i = 0, n = 2; i < n; i++) {}\n";
+
+        assert!(Parser::new(src).parse().is_ok());
+    }
+
+    #[test]
+    fn classes() {
+        test_search_with_args(
+            "data/classes/index.js",
+            "check|constructor",
+            Some("foo"),
+            &["--trace"],
+            vec![
+                "data/classes/user-service.js:2:3: constructor(db) {",
+                "foo -> UserService.create (data/classes/user-service.js:UserService.create) -> new UserService (data/classes/user-service.js:UserService#constructor) -> match at line 2",
+                "data/classes/user-service.js:17:5: check(user.name);",
+                "foo -> new UserService().save (data/classes/user-service.js:UserService#save) -> this.validate (data/classes/user-service.js:UserService#validate) -> match at line 17",
+                "data/classes/user-service.js:25:10: function check(name) {",
+                "-> this.validate (data/classes/user-service.js:UserService#validate) -> check -> match at line 25",
+            ],
+        );
+        test_search_with_args(
+            "data/classes/index.js",
+            "check",
+            Some("bar"),
+            &["--trace"],
+            vec![
+                "data/classes/user-service.js:17:5: check(user.name);",
+                "bar -> svc.validate (data/classes/user-service.js:UserService#validate) -> match at line 17",
+                "data/classes/user-service.js:25:10: function check(name) {",
+                "bar -> svc.validate (data/classes/user-service.js:UserService#validate) -> check -> match at line 25",
+            ],
+        );
+    }
+
+    #[test]
+    fn object_literals() {
+        test_search_with_args(
+            "data/objects.js",
+            "pin",
+            Some("foo"),
+            &["--trace"],
+            vec![
+                "data/objects.js:16:7: get(pin) {",
+                "foo -> api.get -> match at line 16",
+                "data/objects.js:17:17: return load(pin);",
+                "foo -> api.get -> match at line 17",
+                "data/objects.js:3:15: function load(pin) {",
+                "foo -> api.get -> load -> match at line 3",
+                "data/objects.js:4:10: return pin;",
+                "foo -> api.get -> load -> match at line 4",
+                "data/objects.js:19:18: put: function (pin) {",
+                "foo -> api.put -> match at line 19",
+                "data/objects.js:20:12: return pin;",
+                "foo -> api.put -> match at line 20",
+                "data/objects.js:22:12: remove: (pin) => {",
+                "foo -> api.remove -> match at line 22",
+                "data/objects.js:23:12: return pin;",
+                "foo -> api.remove -> match at line 23",
+                "data/objects.js:7:15: function find(pin) {",
+                "foo -> api.fetch (data/objects.js:find) -> match at line 7",
+                "data/objects.js:8:10: return pin;",
+                "foo -> api.fetch (data/objects.js:find) -> match at line 8",
+            ],
+        );
+
+        test_search(
+            "data/objects.js",
+            "pin|key|users|defaults",
+            None,
+            vec![
+                "1:7: const key = \"id\";",
+                "12:11: base: \"/users\",",
+                "13:4: [key]: 1,",
+                "25:6: ...defaults,",
+            ],
+        );
+    }
+
+    #[test]
+    fn function_expressions() {
+        test_search_with_args(
+            "data/functions.js",
+            "scale|x \\*",
+            Some("foo"),
+            &["--trace"],
+            vec![
+                "data/functions.js:2:21: const double = x => scale(x, 2);",
+                "foo -> double -> match at line 2",
+                "data/functions.js:12:10: function scale(x, by) {",
+                "foo -> double -> scale -> match at line 12",
+                "data/functions.js:13:10: return x * by;",
+                "foo -> double -> scale -> match at line 13",
+                "data/functions.js:4:3: scale(x, 0.5);",
+                "foo -> half -> match at line 4",
+                "data/functions.js:6:10: return scale(x, 3);",
+                "foo -> triple -> match at line 6",
+                "data/functions.js:9:10: return x * x;",
+                "foo -> square -> match at line 9",
+                "data/functions.js:25:21: [1, 2].map((n) => scale(n, 1));",
+                "foo -> match at line 25",
+            ],
+        );
+
+        // `other` is never called, so its body is left alone
+        test_search("data/functions.js", "unrelated", None, vec![]);
+    }
+
+    #[test]
+    fn recursion() {
+        test_search_with_args(
+            "data/recursion.js",
+            "log",
+            Some("foo"),
+            &["--trace"],
+            vec!["7:3: log(node);", "foo -> walk -> visit -> match at line 7"],
+        );
+    }
+
+    #[test]
+    fn variable_declarations() {
+        test_search_with_args(
+            "data/declarations.js",
+            "scale",
+            Some("foo"),
+            &["--trace"],
+            vec![
+                "data/declarations.js:2:32: const rate = 2, apply = (n) => scale(n, rate);",
+                "foo -> apply -> match at line 2",
+                "data/declarations.js:6:10: function scale(n, by) {",
+                "foo -> apply -> scale -> match at line 6",
+            ],
+        );
+
+        let (_, errors) = Parser::new("let a;\nvar b, c = 1;\n").parse_recovering();
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn async_functions() {
+        test_search_with_args(
+            "data/async.js",
+            "query|range|next",
+            Some("foo"),
+            &["--trace"],
+            vec![
+                "data/async.js:3:21: const row = await query(id);",
+                "foo -> load -> match at line 3",
+                "data/async.js:18:10: function query(id) {",
+                "foo -> load -> query -> match at line 18",
+                "data/async.js:8:9: await query(row.id);",
+                "foo -> save -> match at line 8",
+                "data/async.js:11:28: const remove = async id => query(id);",
+                "foo -> remove -> match at line 11",
+                "data/async.js:14:10: yield* range(0, 10);",
+                "foo -> ids -> match at line 14",
+                "data/async.js:22:10: function range(from, to) {",
+                "foo -> ids -> range -> match at line 22",
+                "data/async.js:23:13: return db.range(from, to);",
+                "foo -> ids -> range -> match at line 23",
+                "data/async.js:15:9: yield next();",
+                "foo -> ids -> match at line 15",
+                "data/async.js:26:10: function next() {",
+                "foo -> ids -> next -> match at line 26",
+                "data/async.js:27:13: return db.next();",
+                "foo -> ids -> next -> match at line 27",
+            ],
+        );
+
+        let src = std::fs::read_to_string("data/async.js").unwrap();
+        let (ast, errors) = Parser::new(&src).parse_recovering();
+        assert!(errors.is_empty());
+        let flags = |name| match ast.find_function(name) {
+            Some(ASTNode::FunctionStatement(fs)) => (fs.is_async, fs.is_generator),
+            _ => panic!("function {} not found", name),
+        };
+        assert_eq!(flags("load"), (true, false));
+        assert_eq!(flags("save"), (true, false));
+        assert_eq!(flags("remove"), (true, false));
+        assert_eq!(flags("ids"), (false, true));
+        assert_eq!(flags("query"), (false, false));
+    }
+
+    #[test]
+    fn calls_in_expressions() {
+        test_search(
+            "data/expressions.js",
+            "return",
+            Some("foo"),
+            vec![
+                "18:22: function double(n) { return n * 2; }",
+                "19:23: function isValid(n) { return typeof n === \"number\"; }",
+                "20:19: function log(n) { return n; }",
+                "21:23: function compute(n) { return n; }",
+                "22:22: function plural(n) { return n; }",
+                "23:24: function singular(n) { return n; }",
+                "24:21: function first(n) { return n; }",
+                "25:20: function rest(n) { return [n]; }",
+                "26:20: function pick(n) { return n; }",
+                "28:20: function wrap(n) { return n; }",
+                "27:22: function sum(a, b) { return a + b; }",
+                "29:24: function fetchAll(n) { return n; }",
+                "30:24: function store(rows) { return rows; }",
+                "15:3: return format(x);",
+                "31:22: function format(n) { return String(n); }",
+            ],
+        );
+
+        let ast = Parser::new("x = a || b && c ? d : e ** f ** g;\n")
+            .parse()
+            .unwrap();
+        let ASTNode::Program(prog) = ast else {
+            panic!("expected a program");
+        };
+        let Some(ASTNode::AssignmentExpression(assignment)) = prog.lines.first() else {
+            panic!("expected an assignment");
+        };
+        let ASTNode::ConditionalExpression(conditional) = assignment.right.as_ref() else {
+            panic!("expected a conditional");
+        };
+        match conditional.test.as_ref() {
+            ASTNode::BinaryExpression(or) => {
+                assert_eq!(or.operator, "||");
+                assert!(
+                    matches!(or.right.as_ref(), ASTNode::BinaryExpression(and) if and.operator == "&&")
+                );
+            }
+            _ => panic!("expected a binary expression"),
+        }
+        match conditional.alternate.as_ref() {
+            ASTNode::BinaryExpression(pow) => {
+                assert!(matches!(pow.left.as_ref(), ASTNode::Identifier(e) if e.name == "e"));
+                assert!(matches!(pow.right.as_ref(), ASTNode::BinaryExpression(_)));
+            }
+            _ => panic!("expected a binary expression"),
+        }
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let (_, errors) = Parser::new("const a = 'oops\nfoo();\n").parse_recovering();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 1);
+        assert_eq!(errors[0].column, 16);
+        assert_eq!(errors[0].expected, "'''");
+        assert_eq!(errors[0].found, "newline");
+    }
+
+    #[test]
+    fn spans() {
+        let ast = Parser::new("function foo(o) {\n  o.bar.baz(1);\n}\n")
+            .parse()
+            .unwrap();
+        let pos = |line, column, offset| Position {
+            line,
+            column,
+            offset,
+        };
+
+        let func = match ast.find_function("foo") {
+            Some(ASTNode::FunctionStatement(fs)) => fs,
+            _ => panic!("function not found"),
+        };
+        assert_eq!(func.span, Span::new(pos(0, 0, 0), pos(2, 1, 35)));
+
+        let call = match func.body.as_ref() {
+            ASTNode::BlockStatement(bs) => &bs.body[0],
+            _ => panic!("expected a block"),
+        };
+        assert_eq!(call.get_span(), Span::new(pos(1, 2, 20), pos(1, 14, 32)));
+
+        let member = match call {
+            ASTNode::CallExpression(ce) => ce.base.as_ref(),
+            _ => panic!("expected a call"),
+        };
+        assert_eq!(member.get_span(), Span::new(pos(1, 2, 20), pos(1, 11, 29)));
+    }
+
+    #[test]
+    fn parse_recovering() {
+        let src = "function a() {\n  b(c;\n}\nconst { d } e;\nfunction f() {}\n";
+        let (ast, errors) = Parser::new(src).parse_recovering();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].line, errors[0].column), (3, 1));
+        assert_eq!((errors[1].line, errors[1].column), (4, 13));
+        assert!(ast.find_function("a").is_some());
+        assert!(ast.find_function("f").is_some());
+    }
+
+    #[test]
+    fn object_pattern_rest() {
+        let ast = Parser::new("const { a, ...rest } = obj;\n")
+            .parse()
+            .unwrap();
+        let lines = match ast {
+            ASTNode::Program(prog) => prog.lines,
+            _ => panic!("expected a program"),
+        };
+        match &lines[0] {
+            ASTNode::VariableExpression(ve) => match ve.lhs.as_ref() {
+                ASTNode::ObjectPattern(op) => {
+                    assert_eq!(op.get_value("a").map(String::as_str), Some("a"));
+                    assert_eq!(op.rest.as_deref(), Some("rest"));
+                }
+                _ => panic!("expected an object pattern"),
+            },
+            _ => panic!("expected a variable"),
+        }
+    }
+
+    #[test]
+    fn recover_from_parse_errors() {
+        test_search(
+            "data/parse-error/index.js",
+            "user",
+            Some("foo"),
+            vec![
+                "broken(user);",
+                "function broken(user) {",
+                "user.name = \"broken\";",
+                "log(user;",
+                "after(user);",
+                "function after(user) {",
+                "log(user);",
+                "fine(user);",
+                "function fine(user) {",
+                "user.name = \"fine\";",
+            ],
+        );
+
+        let matcher = Matcher::new("user", &MatcherOptions::default()).unwrap();
+        let mut visitor = ASTVisitor::new(matcher);
+        visitor
+            .search("data/parse-error/index.js", Some("foo"))
+            .unwrap();
+
+        let warnings = visitor.warnings();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].ends_with("broken.js:4:1: expected ')', found '}'"));
+        assert!(warnings[1].ends_with("broken.js:6:16: expected '=', found 'user'"));
+    }
+
+    #[test]
+    fn template_literals() {
+        test_search(
+            "data/template.js",
+            "user",
+            Some("foo"),
+            vec![
+                "10:22: log(`Dear ${format(user)},",
+                "1:17: function format(user) {",
+                "2:10: return user.name;",
+                "12:22: const s = `${greet(user)} and {not a call()}`;",
+                "5:16: function greet(user) {",
+                "13:3: user.done = true;",
+            ],
+        );
+        test_search_with_args(
+            "data/template.js",
+            "welcome|call",
+            Some("foo"),
+            &["--match-kind=string"],
+            vec!["11:3: welcome`);", "12:40: const s"],
+        );
+    }
+
+    #[test]
+    fn regex_literals() {
+        test_search(
+            "data/regex.js",
+            "input",
+            Some("foo"),
+            vec![
+                "12:12: validate(input);",
+                "1:19: function validate(input) {",
+                "3:9: check(input);",
+                "7:16: function check(input) {",
+                "8:7: log(input);",
+                "4:21: return /[/]/.test(input) && input.length / 2 > 1;",
+            ],
+        );
+        test_search_with_args(
+            "data/regex.js",
+            "a-z",
+            Some("foo"),
+            &["--match-kind=string"],
+            vec!["2:24: const prefix"],
+        );
+    }
+
+    #[test]
+    fn grouped_by_file() {
+        let matcher = Matcher::new("user", &MatcherOptions::default()).unwrap();
+        let matches = ASTVisitor::new(matcher)
+            .search("data/classes/index.js", Some("foo"))
+            .unwrap();
+        let lines: Vec<_> = group_by_file(&matches)
+            .iter()
+            .map(|m| (utils::relative_path(&m.file_path), m.line_num))
+            .collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                (String::from("data/classes/index.js"), 3),
+                (String::from("data/classes/index.js"), 4),
+                (String::from("data/classes/index.js"), 5),
+                (String::from("data/classes/user-service.js"), 7),
+                (String::from("data/classes/user-service.js"), 8),
+                (String::from("data/classes/user-service.js"), 11),
+                (String::from("data/classes/user-service.js"), 12),
+                (String::from("data/classes/user-service.js"), 16),
+                (String::from("data/classes/user-service.js"), 17),
+                (String::from("data/classes/user-service.js"), 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let out = search_output(
+            "data/import-test.js",
+            "obj.baz",
+            &["-n=foo", "--format=jsonl"],
+        );
+        assert!(!out.contains('\x1b'));
+
+        let values: Vec<serde_json::Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(values.len(), 2);
+        assert!(values[0]["file"]
+            .as_str()
+            .unwrap()
+            .ends_with("data/mixed/baz.js"));
+        assert_eq!(values[0]["line"], 2);
+        assert_eq!(values[0]["column"], 3);
+        assert_eq!(values[0]["text"], "  obj.baz = 1;");
+        assert_eq!(values[0]["function"], "baz");
+        assert_eq!(values[0]["call_path"][1]["name"], "bazz");
+        assert_eq!(values[1]["function"], "baz2");
+    }
+
+    #[test]
+    fn json() {
+        let out = search_output("data/member-expr.js", "obj", &["--format=json"]);
+        let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+        let lines: Vec<_> = value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["line"].as_u64().unwrap())
+            .collect();
+
+        assert_eq!(lines, vec![2, 7, 11]);
+    }
+
+    #[test]
+    fn search_results() {
+        let matcher = Matcher::new("baz", &MatcherOptions::default()).unwrap();
+        let mut visitor = ASTVisitor::new(matcher);
+        let results = visitor.search("data/import-test.js", Some("foo")).unwrap();
+
+        assert_eq!(results.len(), 5);
+
+        let m = &results[2];
+        assert!(m.file_path.ends_with("data/mixed/baz.js"));
+        assert_eq!(m.line_num, 2);
+        assert_eq!(m.line.trim(), "obj.baz = 1;");
+        assert_eq!(m.function.as_deref(), Some("baz"));
+
+        let call_path: Vec<(&str, &str)> = m
+            .call_path
+            .iter()
+            .map(|f| (f.name.as_str(), f.function.as_str()))
+            .collect();
+        assert_eq!(call_path, vec![("foo", "foo"), ("bazz", "baz")]);
+    }
 }